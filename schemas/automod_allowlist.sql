@@ -0,0 +1,3 @@
+CREATE TABLE IF NOT EXISTS automod_allowlist (
+  domain VARCHAR(255) PRIMARY KEY
+);
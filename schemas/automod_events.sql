@@ -0,0 +1,15 @@
+CREATE TABLE IF NOT EXISTS automod_events (
+  id BIGSERIAL PRIMARY KEY,
+  policy_type VARCHAR(32) NOT NULL,
+  user_id BIGINT NOT NULL,
+  channel_id BIGINT, -- NULL when a profile was screened rather than a message
+  action VARCHAR(15) NOT NULL, -- Sanction taken, Delete if only the message was removed, or Shadow
+  indicator VARCHAR(255), -- Word, domain, invite or file that matched
+  timestamp BIGINT NOT NULL
+);
+
+ALTER TABLE automod_events ALTER COLUMN channel_id DROP NOT NULL;
+ALTER TABLE automod_events ADD COLUMN IF NOT EXISTS case_id INT; -- Case opened for the sanction, what pardons are traced back to
+
+CREATE INDEX IF NOT EXISTS automod_events_timestamp_idx ON automod_events (timestamp);
+CREATE INDEX IF NOT EXISTS automod_events_case_id_idx ON automod_events (case_id);
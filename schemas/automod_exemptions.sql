@@ -0,0 +1,14 @@
+CREATE TABLE IF NOT EXISTS automod_exemptions (
+  policy_type VARCHAR(32) NOT NULL, -- "All" exempts from every policy
+  target_type VARCHAR(10) NOT NULL, -- Role, Channel or Category
+  target_id BIGINT NOT NULL,
+  PRIMARY KEY (policy_type, target_type, target_id)
+);
+
+-- Staff roles, skipped once the table has anything in it so removed exemptions stay removed
+INSERT INTO automod_exemptions (policy_type, target_type, target_id)
+SELECT * FROM (VALUES
+  ('All', 'Role', 468842789053136897),
+  ('All', 'Role', 468841295150972929)
+) AS defaults (policy_type, target_type, target_id)
+WHERE NOT EXISTS (SELECT 1 FROM automod_exemptions);
@@ -0,0 +1,8 @@
+CREATE TABLE IF NOT EXISTS automod_feedback (
+  case_id INT PRIMARY KEY, -- One verdict per automod case
+  policy_type VARCHAR(32) NOT NULL,
+  user_id BIGINT NOT NULL,
+  moderator_id BIGINT NOT NULL,
+  timestamp BIGINT NOT NULL
+);
+ALTER TABLE automod_feedback ALTER COLUMN policy_type TYPE VARCHAR(32); -- Match the width automod_policies allows
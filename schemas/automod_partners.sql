@@ -0,0 +1,5 @@
+CREATE TABLE IF NOT EXISTS automod_partners (
+  guild_id BIGINT PRIMARY KEY, -- Invites to these guilds aren't treated as advertising
+  name VARCHAR(100) NOT NULL,
+  added_by BIGINT NOT NULL
+);
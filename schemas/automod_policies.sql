@@ -0,0 +1,20 @@
+CREATE TABLE IF NOT EXISTS automod_policies (
+  policy_type VARCHAR(32) PRIMARY KEY,
+  enabled BOOLEAN NOT NULL DEFAULT TRUE,
+  action VARCHAR(15) NOT NULL,
+  reason VARCHAR(255) NOT NULL,
+  warn_threshold INT NOT NULL,
+  mute_duration BIGINT
+);
+
+ALTER TABLE automod_policies ADD COLUMN IF NOT EXISTS trigger_threshold INT;
+ALTER TABLE automod_policies ADD COLUMN IF NOT EXISTS window_secs INT;
+ALTER TABLE automod_policies ADD COLUMN IF NOT EXISTS shadow BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE automod_policies ADD COLUMN IF NOT EXISTS account_age_days INT;
+ALTER TABLE automod_policies ADD COLUMN IF NOT EXISTS member_age_secs INT;
+ALTER TABLE automod_policies ADD COLUMN IF NOT EXISTS decay_secs INT;
+ALTER TABLE automod_policies ADD COLUMN IF NOT EXISTS delete_only BOOLEAN;
+
+-- A NewAccount threshold of 0 used to stand for delete-only, move those rows over to the flag once
+UPDATE automod_policies SET delete_only = (policy_type = 'NewAccount' AND warn_threshold = 0) WHERE delete_only IS NULL;
+ALTER TABLE automod_policies ALTER COLUMN delete_only SET DEFAULT FALSE, ALTER COLUMN delete_only SET NOT NULL;
@@ -0,0 +1,16 @@
+CREATE TABLE IF NOT EXISTS automod_sources (
+  url VARCHAR(255) PRIMARY KEY,
+  last_success BIGINT,
+  entry_count INT NOT NULL DEFAULT 0,
+  last_error TEXT
+);
+
+-- Defaults only go into an empty table, so a source a moderator removed doesn't come back on the next run
+INSERT INTO automod_sources (url)
+SELECT url FROM (VALUES
+  ('https://raw.githubusercontent.com/Discord-AntiScam/scam-links/main/list.txt'),
+  ('https://raw.githubusercontent.com/mitchellkrogza/Phishing.Database/master/phishing-links-NEW-today.txt'),
+  ('https://raw.githubusercontent.com/RedPanda4552/PandaPhishLists/main/seen-on-discord.txt'),
+  ('https://raw.githubusercontent.com/nwerosama/FishDB/main/domains.txt')
+) AS defaults (url)
+WHERE NOT EXISTS (SELECT 1 FROM automod_sources);
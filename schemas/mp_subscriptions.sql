@@ -0,0 +1,17 @@
+CREATE TABLE IF NOT EXISTS mp_subscriptions (
+  id SERIAL PRIMARY KEY,
+  user_id BIGINT NOT NULL,
+  -- 'player' or 'server'
+  kind VARCHAR(10) NOT NULL,
+  -- In-game name of the player or name of the server
+  target VARCHAR(64) NOT NULL,
+  -- Server subscriptions only, also notify when fewer slots than this are free
+  free_slots INT,
+  last_notified BIGINT NOT NULL DEFAULT 0
+);
+
+-- Names are matched regardless of case, so uniqueness is too. Drops the old case-sensitive constraint and any duplicates it let through
+ALTER TABLE mp_subscriptions DROP CONSTRAINT IF EXISTS mp_subscriptions_user_id_kind_target_key;
+DELETE FROM mp_subscriptions a USING mp_subscriptions b
+WHERE a.user_id = b.user_id AND a.kind = b.kind AND LOWER(a.target) = LOWER(b.target) AND a.id > b.id;
+CREATE UNIQUE INDEX IF NOT EXISTS mp_subscriptions_target ON mp_subscriptions (user_id, kind, LOWER(target));
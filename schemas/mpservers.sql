@@ -9,3 +9,5 @@ CREATE TABLE IF NOT EXISTS mpservers (
   player_data INT[] NOT NULL,
   PRIMARY KEY (name)
 );
+
+ALTER TABLE mpservers ADD COLUMN IF NOT EXISTS join_feed BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,25 @@
+CREATE TABLE IF NOT EXISTS player_samples (
+  server_name VARCHAR(30) NOT NULL,
+  timestamp BIGINT NOT NULL,
+  players INT NOT NULL,
+  PRIMARY KEY (server_name, timestamp)
+);
+
+-- Samples rolled up per hour and per day, `bucket` is the Unix timestamp the period starts at (UTC)
+CREATE TABLE IF NOT EXISTS player_samples_hourly (
+  server_name VARCHAR(30) NOT NULL,
+  bucket BIGINT NOT NULL,
+  avg_players REAL NOT NULL,
+  peak_players INT NOT NULL,
+  samples INT NOT NULL,
+  PRIMARY KEY (server_name, bucket)
+);
+
+CREATE TABLE IF NOT EXISTS player_samples_daily (
+  server_name VARCHAR(30) NOT NULL,
+  bucket BIGINT NOT NULL,
+  avg_players REAL NOT NULL,
+  peak_players INT NOT NULL,
+  samples INT NOT NULL,
+  PRIMARY KEY (server_name, bucket)
+);
@@ -0,0 +1,21 @@
+CREATE TABLE IF NOT EXISTS player_sessions (
+  id SERIAL PRIMARY KEY,
+  server_name VARCHAR(30) NOT NULL,
+  player_name VARCHAR(64) NOT NULL,
+  is_admin BOOLEAN NOT NULL,
+  started_at BIGINT NOT NULL,
+  -- Both are null while the player is still on the server
+  ended_at BIGINT,
+  duration INT
+);
+
+ALTER TABLE player_sessions ADD COLUMN IF NOT EXISTS last_seen BIGINT; -- Latest refresh that saw the player, what an open session counts up to
+
+CREATE INDEX IF NOT EXISTS player_sessions_open ON player_sessions (server_name) WHERE ended_at IS NULL;
+CREATE INDEX IF NOT EXISTS player_sessions_player ON player_sessions (LOWER(player_name));
+CREATE INDEX IF NOT EXISTS player_sessions_server_player ON player_sessions (server_name, player_name);
+
+-- A retried refresh used to be able to open the same session twice, keep the oldest before enforcing one open session per player
+DELETE FROM player_sessions a USING player_sessions b
+WHERE a.ended_at IS NULL AND b.ended_at IS NULL AND a.server_name = b.server_name AND a.player_name = b.player_name AND a.id > b.id;
+CREATE UNIQUE INDEX IF NOT EXISTS player_sessions_one_open ON player_sessions (server_name, player_name) WHERE ended_at IS NULL;
@@ -1,3 +1,5 @@
 CREATE TABLE IF NOT EXISTS prohibited_words (
   word VARCHAR(255) PRIMARY KEY
 );
+
+ALTER TABLE prohibited_words ADD COLUMN IF NOT EXISTS match_mode VARCHAR(15) NOT NULL DEFAULT 'Exact';
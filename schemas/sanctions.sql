@@ -10,3 +10,6 @@ CREATE TABLE IF NOT EXISTS sanctions (
   duration BIGINT,
   reason VARCHAR(255) NOT NULL
 );
+
+-- Pardoned automod cases are kept around for the record
+ALTER TABLE sanctions ADD COLUMN IF NOT EXISTS voided BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,6 @@
+CREATE TABLE IF NOT EXISTS scam_images (
+  hash BIGINT PRIMARY KEY, -- dHash of the image, stored as signed
+  label VARCHAR(255) NOT NULL,
+  added_by BIGINT NOT NULL,
+  added_at BIGINT NOT NULL
+);
@@ -2,3 +2,8 @@ CREATE TABLE IF NOT EXISTS settings (
   id SERIAL PRIMARY KEY,
   logs_ignored_channels BIGINT[] NOT NULL DEFAULT '{}'
 );
+
+-- How many days of player history is kept at each resolution
+ALTER TABLE settings ADD COLUMN IF NOT EXISTS sample_retention_days INT NOT NULL DEFAULT 7;
+ALTER TABLE settings ADD COLUMN IF NOT EXISTS hourly_retention_days INT NOT NULL DEFAULT 90;
+ALTER TABLE settings ADD COLUMN IF NOT EXISTS daily_retention_days INT NOT NULL DEFAULT 730;
@@ -0,0 +1,3 @@
+CREATE TABLE IF NOT EXISTS word_allowlist (
+  word VARCHAR(255) PRIMARY KEY
+);
@@ -1,3 +1,6 @@
+#[cfg(feature = "automod")]
+mod automod;
+mod dehoist;
 mod dev;
 mod moderation;
 mod mp;
@@ -5,7 +8,11 @@ mod ping;
 mod settings;
 mod uptime;
 
+#[cfg(feature = "automod")]
+pub use automod::automod;
+
 pub use {
+  dehoist::dehoist,
   dev::dev,
   moderation::*,
   mp::mp,
@@ -31,6 +38,9 @@ macro_rules! collect {
       commands::case(),
       commands::pw(),
       commands::pu(),
+      commands::dehoist(),
+      #[cfg(feature = "automod")]
+      commands::automod(),
       // unsorted mess
       commands::mp(),
       commands::ping(),
@@ -0,0 +1,978 @@
+use crate::{
+  BotError,
+  commands::ActionType,
+  controllers::{
+    automod::{
+      AutomodExemption,
+      AutomodPolicy,
+      AutomodPolicyType,
+      ExemptionTarget,
+      dhash,
+      parse_domain
+    },
+    sql::{
+      AutomodAllowlist,
+      AutomodEvents,
+      AutomodFeedback,
+      AutomodPartners,
+      AutomodSources,
+      ScamImages
+    }
+  },
+  internals::{
+    canvas::Canvas,
+    config::BINARY_PROPERTIES,
+    utils::format_duration
+  }
+};
+
+use {
+  poise::{
+    CreateReply,
+    serenity_prelude::{
+      Attachment,
+      CreateAttachment,
+      CreateEmbed,
+      CreateEmbedFooter,
+      GenericChannelId,
+      Role
+    }
+  },
+  reqwest::Url,
+  std::{
+    str::FromStr,
+    time::{
+      SystemTime,
+      UNIX_EPOCH
+    }
+  }
+};
+
+#[derive(poise::ChoiceParameter)]
+enum StatsPeriod {
+  Week,
+  Month,
+  Quarter
+}
+
+impl StatsPeriod {
+  fn days(&self) -> i64 {
+    match self {
+      Self::Week => 7,
+      Self::Month => 30,
+      Self::Quarter => 90
+    }
+  }
+}
+
+#[derive(poise::ChoiceParameter)]
+enum PolicyField {
+  Enabled,
+  Action,
+  #[name = "Warn threshold"]
+  WarnThreshold,
+  #[name = "Mute duration"]
+  MuteDuration,
+  #[name = "Warning decay"]
+  Decay,
+  Reason,
+  #[name = "Trigger threshold"]
+  TriggerThreshold,
+  Window,
+  #[name = "Account age"]
+  AccountAge,
+  #[name = "Member age"]
+  MemberAge,
+  #[name = "Delete only"]
+  DeleteOnly,
+  #[name = "Shadow mode"]
+  Shadow
+}
+
+fn parse_bool(value: &str) -> Result<bool, String> {
+  match value.to_lowercase().as_str() {
+    "true" | "on" | "yes" | "enable" | "enabled" => Ok(true),
+    "false" | "off" | "no" | "disable" | "disabled" => Ok(false),
+    _ => Err(format!("`{value}` is not a valid toggle, expected `on` or `off`"))
+  }
+}
+
+/// Apply the new value to the policy, returns the error message if the value is invalid
+fn apply_field(
+  policy: &mut AutomodPolicy,
+  field: &PolicyField,
+  value: &str
+) -> Result<(), String> {
+  match field {
+    PolicyField::Enabled => policy.enabled = parse_bool(value)?,
+    PolicyField::Shadow => policy.shadow = parse_bool(value)?,
+    PolicyField::DeleteOnly => policy.delete_only = parse_bool(value)?,
+    PolicyField::Action => {
+      let action = ActionType::from_str(value)?;
+      if matches!(action, ActionType::Unban | ActionType::Unmute) {
+        return Err(format!("`{action}` can't be used as an automod action"));
+      }
+      if matches!(action, ActionType::Mute) && policy.mute_duration.is_none() {
+        return Err("Set the mute duration first before switching the action to `Mute`".to_string());
+      }
+      policy.action = action;
+    },
+    PolicyField::WarnThreshold => policy.warn_threshold = value.parse::<u32>().map_err(|_| format!("`{value}` is not a valid number"))?,
+    PolicyField::MuteDuration => {
+      if value.eq_ignore_ascii_case("none") {
+        if matches!(policy.action, ActionType::Mute) {
+          return Err("Can't remove the mute duration while the action is `Mute`".to_string());
+        }
+        policy.mute_duration = None;
+      } else {
+        const MAX_TIMEOUT_SECONDS: u64 = 2419200; // 28 days in seconds
+        let d = parse_duration::parse(value).map_err(|e| format!("Could not parse the duration: {e}"))?;
+        if d.as_secs() == 0 || d.as_secs() > MAX_TIMEOUT_SECONDS {
+          return Err("Mute duration must be between 1 second and 28 days".to_string());
+        }
+        policy.mute_duration = Some(d.as_secs() as i64);
+      }
+    },
+    PolicyField::Decay => {
+      const MAX_DECAY_SECONDS: u64 = 604800; // 7 days in seconds
+      let d = parse_duration::parse(value).map_err(|e| format!("Could not parse the duration: {e}"))?;
+      if d.as_secs() == 0 || d.as_secs() > MAX_DECAY_SECONDS {
+        return Err("Warning decay must be between 1 second and 7 days".to_string());
+      }
+      policy.decay_secs = d.as_secs() as u32;
+    },
+    PolicyField::Reason => {
+      if value.is_empty() || value.len() > 255 {
+        return Err("Reason must be between 1 and 255 characters".to_string());
+      }
+      policy.reason = value.to_string();
+    },
+    PolicyField::TriggerThreshold | PolicyField::Window if !policy.policy_type.is_rate_based() => {
+      return Err(format!("**{}** doesn't use a trigger threshold or window", policy.policy_type.as_str()));
+    },
+    PolicyField::TriggerThreshold => {
+      policy.trigger_threshold = match value.parse::<u32>() {
+        Ok(t) if (1..=100).contains(&t) => t,
+        _ => return Err("Trigger threshold must be a number between 1 and 100".to_string())
+      }
+    },
+    PolicyField::Window => {
+      const MAX_WINDOW_SECONDS: u64 = 3600;
+      let d = parse_duration::parse(value).map_err(|e| format!("Could not parse the duration: {e}"))?;
+      if d.as_secs() == 0 || d.as_secs() > MAX_WINDOW_SECONDS {
+        return Err("Window must be between 1 second and 1 hour".to_string());
+      }
+      policy.window_secs = d.as_secs() as u32;
+    },
+    PolicyField::AccountAge | PolicyField::MemberAge if policy.policy_type != AutomodPolicyType::NewAccount => {
+      return Err(format!("**{}** doesn't use account or member age", policy.policy_type.as_str()));
+    },
+    PolicyField::AccountAge => {
+      policy.account_age_days = match value.parse::<u32>() {
+        Ok(d) if d <= 365 => d,
+        _ => return Err("Account age must be a number of days between 0 and 365".to_string())
+      }
+    },
+    PolicyField::MemberAge => {
+      const MAX_MEMBER_AGE_SECONDS: u64 = 604800; // 7 days in seconds
+      let secs = if value == "0" || value.eq_ignore_ascii_case("none") {
+        0
+      } else {
+        parse_duration::parse(value)
+          .map_err(|e| format!("Could not parse the duration: {e}"))?
+          .as_secs()
+      };
+      if secs > MAX_MEMBER_AGE_SECONDS {
+        return Err("Member age must be between 0 and 7 days".to_string());
+      }
+      policy.member_age_secs = secs as u32;
+    }
+  }
+
+  Ok(())
+}
+
+fn policy_summary(policy: &AutomodPolicy) -> String {
+  let mut summary = vec![
+    format!("Enabled: **{}**", if policy.enabled { "Yes" } else { "No" }),
+    format!("Shadow mode: **{}**", if policy.shadow { "Yes" } else { "No" }),
+    format!("Action: **{}**", policy.action),
+    format!("Delete only: **{}**", if policy.delete_only { "Yes" } else { "No" }),
+    format!("Warn threshold: **{}**", policy.warn_threshold),
+    format!("Warning decay: **{}**", format_duration(policy.decay_secs as u64)),
+    format!(
+      "Mute duration: **{}**",
+      policy.mute_duration.map_or("None".to_string(), |d| format_duration(d as u64))
+    ),
+    format!("Reason: `{}`", policy.reason),
+  ];
+
+  if policy.policy_type.is_rate_based() {
+    summary.push(format!(
+      "Trigger: **{}** within **{}**",
+      policy.trigger_threshold,
+      format_duration(policy.window_secs as u64)
+    ));
+  }
+
+  if policy.policy_type == AutomodPolicyType::NewAccount {
+    summary.push(match policy.account_age_days {
+      0 => "Account age: **Off**".to_string(),
+      d => format!("Account age: under **{d}** days")
+    });
+    summary.push(match policy.member_age_secs {
+      0 => "Member age: **Off**".to_string(),
+      s => format!("Member age: under **{}**", format_duration(s as u64))
+    });
+  }
+
+  summary.join("\n")
+}
+
+/// Manage the automoderator
+#[poise::command(
+  slash_command,
+  subcommands("policy", "sources", "allowlist", "exempt", "partners", "images", "test", "stats"),
+  default_member_permissions = "ADMINISTRATOR"
+)]
+pub async fn automod(_: super::PoiseContext<'_>) -> Result<(), BotError> { Ok(()) }
+
+fn source_summary(source: &AutomodSources) -> String {
+  let mut summary = vec![
+    format!(
+      "Last success: {}",
+      source.last_success.map_or("**Never**".to_string(), |t| format!("<t:{t}:R>"))
+    ),
+    format!("Entries: **{}**", source.entry_count),
+  ];
+
+  if let Some(e) = &source.last_error {
+    let e: String = e.chars().take(200).collect();
+    summary.push(format!("Last error: `{e}`"));
+  }
+
+  summary.join("\n")
+}
+
+/// Manage the automod policies
+#[poise::command(slash_command, subcommands("policy_set", "policy_list"))]
+async fn policy(_: super::PoiseContext<'_>) -> Result<(), BotError> { Ok(()) }
+
+/// Change a field on the automod policy
+#[poise::command(slash_command, rename = "set")]
+async fn policy_set(
+  ctx: super::PoiseContext<'_>,
+  #[description = "Policy to change"] policy_type: AutomodPolicyType,
+  #[description = "Field to change"] field: PolicyField,
+  #[description = "New value (e.g. on/off, Mute, 3, 30m, none, 10s)"] value: String
+) -> Result<(), BotError> {
+  let db = &ctx.data().postgres;
+  let policies = AutomodPolicy::load_all(db).await?;
+
+  let mut policy = match policies.into_iter().find(|p| p.policy_type == policy_type) {
+    Some(p) => p,
+    None => {
+      ctx.reply(format!("**{}** policy doesn't exist!", policy_type.as_str())).await?;
+      return Ok(());
+    }
+  };
+
+  if let Err(e) = apply_field(&mut policy, &field, value.trim()) {
+    ctx.send(CreateReply::new().content(e).ephemeral(true)).await?;
+    return Ok(());
+  }
+
+  policy.to_row().upsert(db).await?;
+  ctx.data().automod.reload_policies(db).await?;
+
+  ctx
+    .send(
+      CreateReply::new().embed(
+        CreateEmbed::new()
+          .color(BINARY_PROPERTIES.embed_colors.green)
+          .title(format!("{} policy updated", policy.policy_type.as_str()))
+          .description(policy_summary(&policy))
+      )
+    )
+    .await?;
+
+  Ok(())
+}
+
+/// View the current automod policies
+#[poise::command(slash_command, rename = "list")]
+async fn policy_list(ctx: super::PoiseContext<'_>) -> Result<(), BotError> {
+  let policies = AutomodPolicy::load_all(&ctx.data().postgres).await?;
+  let pardons = AutomodFeedback::pardons_per_policy(&ctx.data().postgres, 0).await?;
+
+  let fields: Vec<(&str, String, bool)> = policies
+    .iter()
+    .map(|p| {
+      let pardoned = pardons.iter().find(|(t, _)| t == p.policy_type.as_str()).map_or(0, |(_, n)| *n);
+      let summary = format!("{}\nPardoned cases: **{pardoned}**", policy_summary(p));
+      (p.policy_type.as_str(), summary, true)
+    })
+    .collect();
+
+  ctx
+    .send(
+      CreateReply::new().embed(
+        CreateEmbed::new()
+          .color(BINARY_PROPERTIES.embed_colors.primary)
+          .title("Automod policies")
+          .fields(fields)
+      )
+    )
+    .await?;
+
+  Ok(())
+}
+
+/// Manage the malicious domain blocklist sources
+#[poise::command(slash_command, subcommands("sources_add", "sources_remove", "sources_list"))]
+async fn sources(_: super::PoiseContext<'_>) -> Result<(), BotError> { Ok(()) }
+
+/// Add a blocklist source (hosts-file, plain list or URL list)
+#[poise::command(slash_command, rename = "add")]
+async fn sources_add(
+  ctx: super::PoiseContext<'_>,
+  #[description = "Raw URL of the blocklist"] url: String
+) -> Result<(), BotError> {
+  let url = url.trim();
+  if !Url::parse(url).is_ok_and(|u| matches!(u.scheme(), "http" | "https")) || url.len() > 255 {
+    ctx.reply("Bad input! Expected a `https://` link to the raw blocklist").await?;
+    return Ok(());
+  }
+
+  ctx.defer().await?;
+  let db = &ctx.data().postgres;
+
+  if !AutomodSources::add_source(db, url).await? {
+    ctx.reply("That source is already on the list!").await?;
+    return Ok(());
+  }
+
+  ctx.data().automod.refresh_malicious_domains(db, true).await?;
+
+  let summary = match AutomodSources::get_sources(db).await?.into_iter().find(|s| s.url == url) {
+    Some(s) => source_summary(&s),
+    None => String::new()
+  };
+
+  ctx
+    .send(
+      CreateReply::new().embed(
+        CreateEmbed::new()
+          .color(BINARY_PROPERTIES.embed_colors.green)
+          .title("Blocklist source added")
+          .description(format!("{url}\n{summary}"))
+      )
+    )
+    .await?;
+
+  Ok(())
+}
+
+/// Remove a blocklist source
+#[poise::command(slash_command, rename = "remove")]
+async fn sources_remove(
+  ctx: super::PoiseContext<'_>,
+  #[description = "Raw URL of the blocklist"] url: String
+) -> Result<(), BotError> {
+  ctx.defer().await?;
+  let db = &ctx.data().postgres;
+
+  if !AutomodSources::remove_source(db, url.trim()).await? {
+    ctx.reply("That source isn't on the list!").await?;
+    return Ok(());
+  }
+
+  // Rebuild without the removed source so its entries don't linger until the next hourly refresh
+  ctx.data().automod.refresh_malicious_domains(db, true).await?;
+  ctx.reply(format!("Removed <{}> from the blocklist sources!", url.trim())).await?;
+
+  Ok(())
+}
+
+/// View the blocklist sources and their health
+#[poise::command(slash_command, rename = "list")]
+async fn sources_list(ctx: super::PoiseContext<'_>) -> Result<(), BotError> {
+  let sources = AutomodSources::get_sources(&ctx.data().postgres).await?;
+  if sources.is_empty() {
+    ctx.reply("No blocklist sources found").await?;
+    return Ok(());
+  }
+
+  let fields: Vec<(&str, String, bool)> = sources.iter().take(25).map(|s| (s.url.as_str(), source_summary(s), false)).collect();
+
+  ctx
+    .send(
+      CreateReply::new().embed(
+        CreateEmbed::new()
+          .color(BINARY_PROPERTIES.embed_colors.primary)
+          .title("Blocklist sources")
+          .fields(fields)
+      )
+    )
+    .await?;
+
+  Ok(())
+}
+
+/// Manage the domains that are never treated as malicious
+#[poise::command(slash_command, subcommands("allowlist_add", "allowlist_remove", "allowlist_list"))]
+async fn allowlist(_: super::PoiseContext<'_>) -> Result<(), BotError> { Ok(()) }
+
+/// Allow a domain (and its subdomains) that a blocklist has flagged by mistake
+#[poise::command(slash_command, rename = "add")]
+async fn allowlist_add(
+  ctx: super::PoiseContext<'_>,
+  #[description = "Domain to allow"] domain: String
+) -> Result<(), BotError> {
+  let Some(domain) = parse_domain(&domain) else {
+    ctx.reply("Bad input! Expected `example.com` or `https://example.com`").await?;
+    return Ok(());
+  };
+
+  let db = &ctx.data().postgres;
+  if AutomodAllowlist::add_domain(db, &domain).await? {
+    ctx.data().automod.reload_allowlist(db).await?;
+    ctx.reply(format!("Added `{domain}` to the allowlist!")).await?;
+  } else {
+    ctx.reply(format!("`{domain}` is already on the allowlist!")).await?;
+  }
+
+  Ok(())
+}
+
+/// Remove a domain from the allowlist
+#[poise::command(slash_command, rename = "remove")]
+async fn allowlist_remove(
+  ctx: super::PoiseContext<'_>,
+  #[description = "Domain to remove"] domain: String
+) -> Result<(), BotError> {
+  let domain = parse_domain(&domain).unwrap_or(domain);
+
+  let db = &ctx.data().postgres;
+  if AutomodAllowlist::remove_domain(db, &domain).await? {
+    ctx.data().automod.reload_allowlist(db).await?;
+    ctx.reply(format!("Removed `{domain}` from the allowlist!")).await?;
+  } else {
+    ctx.reply(format!("`{domain}` isn't on the allowlist!")).await?;
+  }
+
+  Ok(())
+}
+
+/// View the allowlisted domains
+#[poise::command(slash_command, rename = "list")]
+async fn allowlist_list(ctx: super::PoiseContext<'_>) -> Result<(), BotError> {
+  let mut domains: Vec<String> = AutomodAllowlist::get_domains(&ctx.data().postgres)
+    .await?
+    .into_iter()
+    .map(|d| format!("`{}`", d.domain))
+    .collect();
+
+  if domains.is_empty() {
+    ctx.reply("No domains are allowlisted").await?;
+    return Ok(());
+  }
+
+  domains.sort();
+  let mut description = domains.join("\n");
+  if description.len() > 4000 {
+    description.truncate(description[..4000].rfind('\n').unwrap_or(0));
+    description.push_str("\n...");
+  }
+
+  ctx
+    .send(
+      CreateReply::new().embed(
+        CreateEmbed::new()
+          .color(BINARY_PROPERTIES.embed_colors.primary)
+          .title(format!("Allowlisted domains ({})", domains.len()))
+          .description(description)
+      )
+    )
+    .await?;
+
+  Ok(())
+}
+
+fn exemption_target(
+  role: Option<Role>,
+  channel: Option<GenericChannelId>,
+  category: Option<GenericChannelId>
+) -> Result<(ExemptionTarget, u64), &'static str> {
+  match (role, channel, category) {
+    (Some(r), None, None) => Ok((ExemptionTarget::Role, r.id.get())),
+    (None, Some(c), None) => Ok((ExemptionTarget::Channel, c.get())),
+    (None, None, Some(c)) => Ok((ExemptionTarget::Category, c.get())),
+    _ => Err("Pick exactly one role, channel or category")
+  }
+}
+
+fn exemption_mention(exemption: &AutomodExemption) -> String {
+  match exemption.target {
+    ExemptionTarget::Role => format!("<@&{}>", exemption.target_id),
+    ExemptionTarget::Channel | ExemptionTarget::Category => format!("<#{}>", exemption.target_id)
+  }
+}
+
+/// Manage which roles, channels and categories the policies don't apply to
+#[poise::command(slash_command, subcommands("exempt_add", "exempt_remove", "exempt_list"))]
+async fn exempt(_: super::PoiseContext<'_>) -> Result<(), BotError> { Ok(()) }
+
+/// Exempt a role, channel or category from a policy
+#[poise::command(slash_command, rename = "add")]
+async fn exempt_add(
+  ctx: super::PoiseContext<'_>,
+  #[description = "Policy to exempt from, leave empty for every policy"] policy_type: Option<AutomodPolicyType>,
+  #[description = "Role to exempt"] role: Option<Role>,
+  #[description = "Channel to exempt, including its threads"]
+  #[channel_types("Text", "News", "Forum", "Voice")]
+  channel: Option<GenericChannelId>,
+  #[description = "Category to exempt"]
+  #[channel_types("Category")]
+  category: Option<GenericChannelId>
+) -> Result<(), BotError> {
+  let (target, target_id) = match exemption_target(role, channel, category) {
+    Ok(t) => t,
+    Err(e) => {
+      ctx.send(CreateReply::new().content(e).ephemeral(true)).await?;
+      return Ok(());
+    }
+  };
+
+  let exemption = AutomodExemption {
+    policy_type,
+    target,
+    target_id
+  };
+  let policy = exemption.policy_type.as_ref().map_or("every policy", |p| p.as_str());
+
+  let db = &ctx.data().postgres;
+  if exemption.to_row().add(db).await? {
+    ctx.data().automod.reload_exemptions(db).await?;
+    ctx
+      .reply(format!("{} is now exempt from **{policy}**", exemption_mention(&exemption)))
+      .await?;
+  } else {
+    ctx
+      .reply(format!("{} is already exempt from **{policy}**", exemption_mention(&exemption)))
+      .await?;
+  }
+
+  Ok(())
+}
+
+/// Remove an exemption
+#[poise::command(slash_command, rename = "remove")]
+async fn exempt_remove(
+  ctx: super::PoiseContext<'_>,
+  #[description = "Policy the exemption is for, leave empty for every policy"] policy_type: Option<AutomodPolicyType>,
+  #[description = "Exempted role"] role: Option<Role>,
+  #[description = "Exempted channel"]
+  #[channel_types("Text", "News", "Forum", "Voice")]
+  channel: Option<GenericChannelId>,
+  #[description = "Exempted category"]
+  #[channel_types("Category")]
+  category: Option<GenericChannelId>
+) -> Result<(), BotError> {
+  let (target, target_id) = match exemption_target(role, channel, category) {
+    Ok(t) => t,
+    Err(e) => {
+      ctx.send(CreateReply::new().content(e).ephemeral(true)).await?;
+      return Ok(());
+    }
+  };
+
+  let exemption = AutomodExemption {
+    policy_type,
+    target,
+    target_id
+  };
+  let policy = exemption.policy_type.as_ref().map_or("every policy", |p| p.as_str());
+
+  let db = &ctx.data().postgres;
+  if exemption.to_row().remove(db).await? {
+    ctx.data().automod.reload_exemptions(db).await?;
+    ctx
+      .reply(format!("{} is no longer exempt from **{policy}**", exemption_mention(&exemption)))
+      .await?;
+  } else {
+    ctx
+      .reply(format!("{} isn't exempt from **{policy}**", exemption_mention(&exemption)))
+      .await?;
+  }
+
+  Ok(())
+}
+
+/// View the current exemptions
+#[poise::command(slash_command, rename = "list")]
+async fn exempt_list(ctx: super::PoiseContext<'_>) -> Result<(), BotError> {
+  let exemptions = AutomodExemption::load_all(&ctx.data().postgres).await?;
+  if exemptions.is_empty() {
+    ctx.reply("Nothing is exempt from the automod").await?;
+    return Ok(());
+  }
+
+  let mut fields: Vec<(&str, String, bool)> = Vec::new();
+  let groups = std::iter::once(None).chain(AutomodPolicy::defaults().into_iter().map(|p| Some(p.policy_type)));
+
+  for policy_type in groups {
+    let entries: Vec<String> = exemptions
+      .iter()
+      .filter(|e| e.policy_type == policy_type)
+      .map(|e| format!("{} ({})", exemption_mention(e), e.target.as_str()))
+      .collect();
+
+    if !entries.is_empty() {
+      fields.push((policy_type.map_or("All policies", |p| p.as_str()), entries.join("\n"), true));
+    }
+  }
+
+  ctx
+    .send(
+      CreateReply::new().embed(
+        CreateEmbed::new()
+          .color(BINARY_PROPERTIES.embed_colors.primary)
+          .title("Automod exemptions")
+          .fields(fields)
+      )
+    )
+    .await?;
+
+  Ok(())
+}
+
+/// Manage the partner servers whose invite links are allowed
+#[poise::command(slash_command, subcommands("partners_add", "partners_remove", "partners_list"))]
+async fn partners(_: super::PoiseContext<'_>) -> Result<(), BotError> { Ok(()) }
+
+/// Allow invite links to a partner server
+#[poise::command(slash_command, rename = "add")]
+async fn partners_add(
+  ctx: super::PoiseContext<'_>,
+  #[description = "An invite link to the partner server"] invite: String
+) -> Result<(), BotError> {
+  let code = invite.trim().trim_end_matches('/').rsplit('/').next().unwrap_or_default();
+
+  let guild = match ctx.http().get_invite(code, false, false, None).await {
+    Ok(i) => i.guild,
+    Err(e) => {
+      ctx
+        .send(CreateReply::new().content(format!("Couldn't resolve that invite: {e}")).ephemeral(true))
+        .await?;
+      return Ok(());
+    }
+  };
+
+  let Some(guild) = guild else {
+    ctx
+      .send(CreateReply::new().content("That invite doesn't lead to a server").ephemeral(true))
+      .await?;
+    return Ok(());
+  };
+
+  let partner = AutomodPartners {
+    guild_id: guild.id.get() as i64,
+    name:     guild.name.to_string(),
+    added_by: ctx.author().id.get() as i64
+  };
+
+  let db = &ctx.data().postgres;
+  if partner.add(db).await? {
+    ctx.data().automod.reload_partners(db).await?;
+    ctx
+      .reply(format!("Invites to **{}** (`{}`) are now allowed!", partner.name, guild.id))
+      .await?;
+  } else {
+    ctx.reply(format!("**{}** is already a partner!", partner.name)).await?;
+  }
+
+  Ok(())
+}
+
+/// Stop allowing invite links to a partner server
+#[poise::command(slash_command, rename = "remove")]
+async fn partners_remove(
+  ctx: super::PoiseContext<'_>,
+  #[description = "ID of the partner server"] guild_id: String
+) -> Result<(), BotError> {
+  let Ok(guild_id) = guild_id.trim().parse::<u64>() else {
+    ctx
+      .send(CreateReply::new().content("Bad input! Expected a server ID").ephemeral(true))
+      .await?;
+    return Ok(());
+  };
+
+  let db = &ctx.data().postgres;
+  if AutomodPartners::remove(db, guild_id as i64).await? {
+    ctx.data().automod.reload_partners(db).await?;
+    ctx.reply(format!("Removed `{guild_id}` from the partners!")).await?;
+  } else {
+    ctx.reply(format!("`{guild_id}` isn't a partner!")).await?;
+  }
+
+  Ok(())
+}
+
+/// View the partner servers
+#[poise::command(slash_command, rename = "list")]
+async fn partners_list(ctx: super::PoiseContext<'_>) -> Result<(), BotError> {
+  let partners = AutomodPartners::get_partners(&ctx.data().postgres).await?;
+  if partners.is_empty() {
+    ctx.reply("No partner servers found").await?;
+    return Ok(());
+  }
+
+  let mut description = String::new();
+  for p in partners.iter() {
+    let line = format!("**{}** `{}`\n", p.name, p.guild_id);
+    if description.len() + line.len() > 4000 {
+      description.push_str("...");
+      break;
+    }
+    description.push_str(&line);
+  }
+
+  ctx
+    .send(
+      CreateReply::new().embed(
+        CreateEmbed::new()
+          .color(BINARY_PROPERTIES.embed_colors.primary)
+          .title(format!("Partner servers ({})", partners.len()))
+          .description(description)
+      )
+    )
+    .await?;
+
+  Ok(())
+}
+
+/// Manage the known scam images
+#[poise::command(slash_command, subcommands("images_add", "images_remove", "images_list"))]
+async fn images(_: super::PoiseContext<'_>) -> Result<(), BotError> { Ok(()) }
+
+/// Add a scam image, edited or rescaled copies of it will be caught too
+#[poise::command(slash_command, rename = "add")]
+async fn images_add(
+  ctx: super::PoiseContext<'_>,
+  #[description = "Screenshot of the scam"] image: Attachment,
+  #[description = "What the scam is, e.g. Free Nitro"] label: String
+) -> Result<(), BotError> {
+  if label.is_empty() || label.len() > 255 {
+    ctx
+      .send(CreateReply::new().content("Label must be between 1 and 255 characters").ephemeral(true))
+      .await?;
+    return Ok(());
+  }
+
+  ctx.defer().await?;
+
+  let data = image.download().await?;
+  let Some(hash) = tokio::task::spawn_blocking(move || dhash(&data)).await? else {
+    ctx.reply("Couldn't read that image, try a PNG or JPEG").await?;
+    return Ok(());
+  };
+
+  let row = ScamImages {
+    hash:     hash as i64,
+    label:    label.clone(),
+    added_by: ctx.author().id.get() as i64,
+    added_at: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64
+  };
+
+  let db = &ctx.data().postgres;
+  if row.add(db).await? {
+    ctx.data().automod.reload_scam_images(db).await?;
+    ctx.reply(format!("Added the image as **{label}** (`{hash:016x}`)")).await?;
+  } else {
+    ctx.reply(format!("That image is already on the list (`{hash:016x}`)")).await?;
+  }
+
+  Ok(())
+}
+
+/// Remove the scam images with the given label
+#[poise::command(slash_command, rename = "remove")]
+async fn images_remove(
+  ctx: super::PoiseContext<'_>,
+  #[description = "Label of the images to remove"] label: String
+) -> Result<(), BotError> {
+  let db = &ctx.data().postgres;
+
+  match ScamImages::remove_label(db, &label).await? {
+    0 => ctx.reply(format!("No images are labelled **{label}**")).await?,
+    n => {
+      ctx.data().automod.reload_scam_images(db).await?;
+      ctx.reply(format!("Removed **{n}** image(s) labelled **{label}**")).await?
+    }
+  };
+
+  Ok(())
+}
+
+/// View the known scam images
+#[poise::command(slash_command, rename = "list")]
+async fn images_list(ctx: super::PoiseContext<'_>) -> Result<(), BotError> {
+  let images = ScamImages::get_images(&ctx.data().postgres).await?;
+  if images.is_empty() {
+    ctx.reply("No scam images found").await?;
+    return Ok(());
+  }
+
+  let mut description = String::new();
+  for image in images.iter() {
+    let line = format!(
+      "**{}** `{:016x}` by <@{}> <t:{}:R>\n",
+      image.label, image.hash as u64, image.added_by, image.added_at
+    );
+    if description.len() + line.len() > 4000 {
+      description.push_str("...");
+      break;
+    }
+    description.push_str(&line);
+  }
+
+  ctx
+    .send(
+      CreateReply::new().embed(
+        CreateEmbed::new()
+          .color(BINARY_PROPERTIES.embed_colors.primary)
+          .title(format!("Scam images ({})", images.len()))
+          .description(description)
+      )
+    )
+    .await?;
+
+  Ok(())
+}
+
+/// Check which policies some text would trip, without acting on it
+#[poise::command(slash_command)]
+async fn test(
+  ctx: super::PoiseContext<'_>,
+  #[description = "Text to run through the automod"] text: String
+) -> Result<(), BotError> {
+  let matches = ctx.data().automod.dry_run(&text).await;
+
+  let embed = if matches.is_empty() {
+    CreateEmbed::new()
+      .color(BINARY_PROPERTIES.embed_colors.green)
+      .title("No policies matched")
+  } else {
+    let fields: Vec<(String, String, bool)> = matches
+      .into_iter()
+      .map(|(policy, reason)| {
+        let name = format!("{}{}", policy.policy_type.as_str(), if policy.shadow { " (shadow)" } else { "" });
+        (name, format!("{reason}\nAction: **{}**", policy.action), false)
+      })
+      .collect();
+
+    CreateEmbed::new()
+      .color(BINARY_PROPERTIES.embed_colors.red)
+      .title("Matched policies")
+      .fields(fields)
+  };
+
+  ctx
+    .send(
+      CreateReply::new()
+        .embed(embed.footer(CreateEmbedFooter::new(
+          "Rate-based, attachment and new account policies aren't covered by dry runs"
+        )))
+        .ephemeral(true)
+    )
+    .await?;
+
+  Ok(())
+}
+
+fn format_top(entries: &[(String, i64)]) -> String {
+  if entries.is_empty() {
+    return "*None*".to_string();
+  }
+
+  entries
+    .iter()
+    .map(|(indicator, hits)| {
+      let indicator: String = indicator.chars().take(60).collect();
+      format!("`{indicator}` ({hits})")
+    })
+    .collect::<Vec<_>>()
+    .join("\n")
+}
+
+/// View what the automod has been catching and how often it got it wrong
+#[poise::command(slash_command)]
+async fn stats(
+  ctx: super::PoiseContext<'_>,
+  #[description = "How far back to look, defaults to a week"] period: Option<StatsPeriod>
+) -> Result<(), BotError> {
+  ctx.defer().await?;
+
+  let days = period.unwrap_or(StatsPeriod::Week).days();
+  let today = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64 / 86400;
+  let first_day = today - days + 1;
+  let since = first_day * 86400;
+
+  let db = &ctx.data().postgres;
+  let counts = AutomodEvents::counts_per_policy(db, since).await?;
+  if counts.is_empty() {
+    ctx.reply(format!("Nothing has been caught in the last {days} days")).await?;
+    return Ok(());
+  }
+
+  let pardons = AutomodFeedback::pardons_per_policy(db, since).await?;
+  let domains = AutomodEvents::top_indicators(db, since, &["MaliciousLinks", "ProhibitedUrls"], 5).await?;
+  let words = AutomodEvents::top_indicators(db, since, &["ProhibitedWords"], 5).await?;
+  let daily = AutomodEvents::daily_counts(db, since).await?;
+
+  let policies = counts
+    .iter()
+    .map(|c| {
+      let pardoned = pardons.iter().find(|(t, _)| *t == c.policy_type).map_or(0, |(_, n)| *n);
+      // Pardons can only happen on sanctioned cases
+      let fp_rate = match c.sanctioned {
+        0 => "n/a".to_string(),
+        n => format!("{:.1}%", pardoned as f64 / n as f64 * 100.0)
+      };
+      format!(
+        "**{}**: {} caught, {} sanctioned, {pardoned} pardoned ({fp_rate} false positives)",
+        c.policy_type, c.total, c.sanctioned
+      )
+    })
+    .collect::<Vec<_>>()
+    .join("\n");
+
+  let mut series = vec![0.0; days as usize];
+  for (day, hits) in daily {
+    if let Some(slot) = series.get_mut((day - first_day) as usize) {
+      *slot = hits as f64;
+    }
+  }
+
+  // Round the ceiling up to a multiple of 8 so the graph gets clean intervals
+  let peak = series.iter().copied().fold(0.0, f64::max);
+  let mut canvas = Canvas::new().ceiling(((peak / 8.0).ceil() * 8.0).max(8.0));
+  canvas.render(series);
+  let file = "AutomodStats.jpg";
+
+  let total: i64 = counts.iter().map(|c| c.total).sum();
+  let embed = CreateEmbed::new()
+    .color(BINARY_PROPERTIES.embed_colors.primary)
+    .title(format!("Automod stats | Last {days} days"))
+    .description(policies)
+    .fields(vec![("Top domains", format_top(&domains), true), ("Top words", format_top(&words), true)])
+    .image(format!("attachment://{file}"))
+    .footer(CreateEmbedFooter::new(format!("{total} detections, graphed per day")));
+
+  ctx
+    .send(CreateReply::new().embed(embed).attachment(CreateAttachment::bytes(canvas.export(), file)))
+    .await?;
+
+  Ok(())
+}
@@ -0,0 +1,109 @@
+use crate::{
+  BotError,
+  internals::{
+    config::BINARY_PROPERTIES,
+    nickname
+  }
+};
+
+#[cfg(not(feature = "automod"))]
+use crate::internals::word_filter::WordFilter;
+
+use {
+  futures::StreamExt,
+  poise::{
+    CreateReply,
+    serenity_prelude::{
+      CreateAttachment,
+      CreateMessage,
+      GenericChannelId
+    }
+  }
+};
+
+/// Clean up hoisted and unreadable nicknames
+#[poise::command(slash_command, subcommands("all"), default_member_permissions = "MANAGE_NICKNAMES")]
+pub async fn dehoist(_: super::PoiseContext<'_>) -> Result<(), BotError> { Ok(()) }
+
+/// Sweep through the whole member list and sanitise every nickname
+#[poise::command(slash_command)]
+async fn all(ctx: super::PoiseContext<'_>) -> Result<(), BotError> {
+  ctx.defer().await?;
+
+  let guild_id = ctx.guild_id().unwrap();
+
+  #[cfg(feature = "automod")]
+  let prohibited = ctx.data().automod.word_filter();
+
+  #[cfg(not(feature = "automod"))]
+  let prohibited = WordFilter::load(&ctx.data().postgres).await?;
+
+  let mut changes = Vec::new();
+  let mut failed = 0;
+  let mut interrupted = false;
+  let mut members = guild_id.members_iter(ctx.http()).boxed();
+
+  while let Some(member) = members.next().await {
+    let member = match member {
+      Ok(m) => m,
+      Err(e) => {
+        eprintln!("Dehoist[Error] Failed to fetch the member list: {e}");
+        interrupted = true;
+        break;
+      }
+    };
+
+    // Changes are logged in bulk once the sweep is done
+    match nickname::enforce(ctx.http(), &member, &prohibited, false).await {
+      Ok(Some((old, new))) => changes.push(format!("{} ({}): \"{old}\" -> \"{new}\"", member.user.name, member.user.id)),
+      Ok(None) => (),
+      Err(e) => {
+        eprintln!("Dehoist[Error] Failed to rename {}: {e}", member.user.name);
+        failed += 1;
+      }
+    }
+  }
+
+  // The rest of the member list was never reached, so say so rather than pass it off as a full sweep
+  let cut_short = if interrupted {
+    " (stopped early, the member list couldn't be fetched)"
+  } else {
+    ""
+  };
+
+  if !changes.is_empty() {
+    let content = format!(
+      "Nickname sweep by {}\n- Total: {}\n\n{}",
+      ctx.author().name,
+      changes.len(),
+      changes.join("\n")
+    );
+
+    GenericChannelId::new(BINARY_PROPERTIES.bot_log)
+      .send_message(
+        ctx.http(),
+        CreateMessage::new()
+          .content(format!(
+            "**{}** ran a nickname sweep, **{}** members renamed{cut_short}",
+            ctx.author().name,
+            changes.len()
+          ))
+          .add_file(CreateAttachment::bytes(content.into_bytes(), "dehoist.txt"))
+      )
+      .await?;
+  }
+
+  ctx
+    .send(CreateReply::new().content(format!(
+      "Sweep finished, renamed **{}** members{}{cut_short}",
+      changes.len(),
+      if failed > 0 {
+        format!(" (**{failed}** couldn't be renamed)")
+      } else {
+        String::new()
+      }
+    )))
+    .await?;
+
+  Ok(())
+}
@@ -3,11 +3,16 @@ use crate::{
   controllers::sql::{
     ProhibitedUrls,
     ProhibitedWords,
-    Sanctions
+    Sanctions,
+    WordAllowlist
   },
   internals::{
     config::BINARY_PROPERTIES,
-    utils::format_duration
+    utils::format_duration,
+    word_filter::{
+      self,
+      MatchMode
+    }
   }
 };
 
@@ -79,6 +84,23 @@ impl std::fmt::Display for ActionType {
   }
 }
 
+impl std::str::FromStr for ActionType {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s.to_lowercase().as_str() {
+      "ban" => Ok(Self::Ban),
+      "softban" => Ok(Self::Softban),
+      "unban" => Ok(Self::Unban),
+      "warn" => Ok(Self::Warn),
+      "kick" => Ok(Self::Kick),
+      "mute" => Ok(Self::Mute),
+      "unmute" => Ok(Self::Unmute),
+      _ => Err(format!("`{s}` is not a valid action type"))
+    }
+  }
+}
+
 pub enum Target {
   User(User),
   Member(Member)
@@ -209,7 +231,8 @@ async fn log_entry(
     timestamp,
     end_time: None,
     duration,
-    reason: reason.into()
+    reason: reason.into(),
+    voided: false
   };
 
   let mut fields = vec![
@@ -689,7 +712,11 @@ async fn view(
 
       let embed = CreateEmbed::default()
         .color(BINARY_PROPERTIES.embed_colors.primary)
-        .title(format!("{} | Case #{case_id}", sanctions.case_type))
+        .title(format!(
+          "{} | Case #{case_id}{}",
+          sanctions.case_type,
+          if sanctions.voided { " (Voided)" } else { "" }
+        ))
         .timestamp(Timestamp::from_unix_timestamp(sanctions.timestamp).unwrap())
         .fields(fields);
 
@@ -808,7 +835,7 @@ enum ProhibitedType {
 
 enum CmdOperation {
   List,
-  Manage(String)
+  Manage(String, Option<MatchMode>)
 }
 
 async fn mpl(
@@ -819,7 +846,7 @@ async fn mpl(
   let db = ctx.data().postgres.clone();
 
   match operation {
-    CmdOperation::Manage(input) => {
+    CmdOperation::Manage(input, mode) => {
       let (normalized_input, original_input) = match item_type {
         ProhibitedType::Word => (input.clone(), input.clone()),
         ProhibitedType::Url => {
@@ -845,8 +872,16 @@ async fn mpl(
             ProhibitedWords::remove_word(&db, &normalized_input).await?;
             ctx.reply(format!("Removed `{normalized_input}` from the list!")).await?;
           } else {
-            ProhibitedWords::add_word(&db, &normalized_input).await?;
-            ctx.reply(format!("Added `{normalized_input}` to the list!")).await?;
+            let mode = mode.unwrap_or(MatchMode::Exact);
+            if let Err(e) = word_filter::validate(&normalized_input, mode) {
+              ctx.send(CreateReply::new().content(e).ephemeral(true)).await?;
+              return Ok(());
+            }
+
+            ProhibitedWords::add_word(&db, &normalized_input, mode.as_str()).await?;
+            ctx
+              .reply(format!("Added `{normalized_input}` to the list! ({} match)", mode.as_str()))
+              .await?;
           }
         },
         ProhibitedType::Url => {
@@ -868,6 +903,13 @@ async fn mpl(
           }
         },
       }
+
+      // Swap the compiled lists in the running Automoderator
+      #[cfg(feature = "automod")]
+      match item_type {
+        ProhibitedType::Word => ctx.data().automod.reload_words(&db).await?,
+        ProhibitedType::Url => ctx.data().automod.reload_urls(&db).await?
+      }
     },
     CmdOperation::List => {
       ctx.defer().await?;
@@ -882,10 +924,28 @@ async fn mpl(
             return Ok(());
           }
 
-          let mut sorted_words: Vec<String> = words.into_iter().map(|w| w.word).collect();
+          let mut sorted_words: Vec<String> = words
+            .into_iter()
+            .map(|w| {
+              if w.match_mode == MatchMode::Exact.as_str() {
+                w.word
+              } else {
+                format!("{} ({})", w.word, w.match_mode)
+              }
+            })
+            .collect();
           sorted_words.sort();
 
-          let content = format!("Prohibited words\n- Total: {}\n\n{}", sorted_words.len(), sorted_words.join("\n"));
+          let mut allowlist: Vec<String> = WordAllowlist::get_words(&db).await?.into_iter().map(|w| w.word).collect();
+          allowlist.sort();
+
+          let content = format!(
+            "Prohibited words\n- Total: {}\n\n{}\n\nAllowlisted words\n- Total: {}\n\n{}",
+            sorted_words.len(),
+            sorted_words.join("\n"),
+            allowlist.len(),
+            allowlist.join("\n")
+          );
 
           let mut temp_file = File::create(pw).await?;
           temp_file.write_all(content.as_bytes()).await?;
@@ -921,7 +981,7 @@ async fn mpl(
 }
 
 /// Prohibited words management
-#[poise::command(slash_command, subcommands("pwm", "pwl"), default_member_permissions = "ADMINISTRATOR")]
+#[poise::command(slash_command, subcommands("pwm", "pwa", "pwl"), default_member_permissions = "ADMINISTRATOR")]
 pub async fn pw(_: super::PoiseContext<'_>) -> Result<(), BotError> { Ok(()) }
 
 /// Prohibited urls management
@@ -932,9 +992,31 @@ pub async fn pu(_: super::PoiseContext<'_>) -> Result<(), BotError> { Ok(()) }
 #[poise::command(slash_command, rename = "manage")]
 async fn pwm(
   ctx: super::PoiseContext<'_>,
-  #[description = "The word to be added or removed"] word: String
+  #[description = "The word to be added or removed"] word: String,
+  #[description = "How the word is matched when adding it, defaults to Exact"] mode: Option<MatchMode>
+) -> Result<(), BotError> {
+  mpl(ctx, ProhibitedType::Word, CmdOperation::Manage(word, mode)).await
+}
+
+/// Add/remove a word that should never be flagged, e.g. Scunthorpe
+#[poise::command(slash_command, rename = "allow")]
+async fn pwa(
+  ctx: super::PoiseContext<'_>,
+  #[description = "The word to be allowed or disallowed"] word: String
 ) -> Result<(), BotError> {
-  mpl(ctx, ProhibitedType::Word, CmdOperation::Manage(word)).await
+  let db = &ctx.data().postgres;
+
+  if WordAllowlist::remove_word(db, &word).await? {
+    ctx.reply(format!("Removed `{word}` from the allowlist!")).await?;
+  } else {
+    WordAllowlist::add_word(db, &word).await?;
+    ctx.reply(format!("Added `{word}` to the allowlist!")).await?;
+  }
+
+  #[cfg(feature = "automod")]
+  ctx.data().automod.reload_words(db).await?;
+
+  Ok(())
 }
 
 /// Add/remove a domain to Automoderator's PU list
@@ -943,7 +1025,7 @@ async fn pum(
   ctx: super::PoiseContext<'_>,
   #[description = "The domain to be added or removed"] url: String
 ) -> Result<(), BotError> {
-  mpl(ctx, ProhibitedType::Url, CmdOperation::Manage(url)).await
+  mpl(ctx, ProhibitedType::Url, CmdOperation::Manage(url, None)).await
 }
 
 /// Retrieve the Automoderator's PW list
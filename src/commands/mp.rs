@@ -1,6 +1,14 @@
 use crate::{
   BotError,
-  controllers::sql::MpServers,
+  controllers::sql::{
+    HourlyPlayers,
+    MpServers,
+    MpSubscriptions,
+    PlayerRetention,
+    PlayerSamples,
+    PlayerSessions,
+    Settings
+  },
   internals::{
     ansi::Color,
     canvas::Canvas,
@@ -40,12 +48,63 @@ use {
       RoleId
     }
   },
-  std::borrow::Cow
+  std::{
+    borrow::Cow,
+    time::{
+      SystemTime,
+      UNIX_EPOCH
+    }
+  }
 };
 
 const CATEGORY_FILTER: [&str; 5] = ["PALLETS", "PALLETSILAGE", "BIGBAGS", "BIGBAGPALLETS", "IBC"];
 const URL_EXTRACTION_FAILED: &str = "Couldn't parse the provided URL, please check and try again.";
 const SERVER_OFFLINE_TEXT: &str = "**{{ name }}** is currently offline!";
+/// `/mp stats` reads the hourly history, so it has to cover the longest period
+const MIN_HOURLY_RETENTION_DAYS: i32 = 30;
+const LEADERBOARD_SIZE: i64 = 10;
+const RECENT_SESSIONS: i64 = 5;
+const MAX_SUBSCRIPTIONS: usize = 10;
+
+#[derive(poise::ChoiceParameter)]
+enum SubscriptionKind {
+  Player,
+  Server
+}
+
+impl SubscriptionKind {
+  fn as_str(&self) -> &'static str {
+    match self {
+      Self::Player => MpSubscriptions::PLAYER,
+      Self::Server => MpSubscriptions::SERVER
+    }
+  }
+}
+
+#[derive(poise::ChoiceParameter)]
+enum StatsPeriod {
+  Day,
+  Week,
+  Month
+}
+
+impl StatsPeriod {
+  fn days(&self) -> i64 {
+    match self {
+      Self::Day => 1,
+      Self::Week => 7,
+      Self::Month => 30
+    }
+  }
+
+  fn label(&self) -> &'static str {
+    match self {
+      Self::Day => "24 hours",
+      Self::Week => "7 days",
+      Self::Month => "30 days"
+    }
+  }
+}
 
 trait IsVowel {
   fn is_vowel(&self) -> bool;
@@ -139,7 +198,10 @@ async fn is_channel_allowed(ctx: super::PoiseContext<'_>) -> bool {
 }
 
 /// Retrieve specific information from FSMP server(s)
-#[poise::command(slash_command, subcommands("players", "details", "pallets", "poll", "tools"))]
+#[poise::command(
+  slash_command,
+  subcommands("players", "details", "pallets", "stats", "leaderboard", "player", "notify", "poll", "tools")
+)]
 pub async fn mp(_: super::PoiseContext<'_>) -> Result<(), BotError> { Ok(()) }
 
 async fn data_warehouse(
@@ -297,6 +359,331 @@ async fn players(
   Ok(())
 }
 
+/// Average player count for each hour of the day (UTC), busiest first
+fn busiest_hours(hours: &[HourlyPlayers]) -> Vec<(usize, f64)> {
+  let mut by_hour = [(0.0, 0); 24];
+  for h in hours {
+    let slot = &mut by_hour[(h.bucket % 86400 / 3600) as usize];
+    slot.0 += h.avg_players as f64 * h.samples as f64;
+    slot.1 += h.samples as i64;
+  }
+
+  let mut ranked: Vec<(usize, f64)> = by_hour
+    .iter()
+    .enumerate()
+    .filter(|(_, (_, samples))| *samples > 0)
+    .map(|(hour, (total, samples))| (hour, total / *samples as f64))
+    .collect();
+  ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+  ranked
+}
+
+/// Shows how busy the given server has been over a period
+#[poise::command(slash_command)]
+async fn stats(
+  ctx: super::PoiseContext<'_>,
+  #[description = "What server to get statistics for"]
+  #[autocomplete = "ac_serverlist"]
+  server: String,
+  #[description = "How far back to look, defaults to a week"] period: Option<StatsPeriod>
+) -> Result<(), BotError> {
+  if !is_channel_allowed(ctx).await {
+    return Ok(());
+  }
+
+  ctx.defer().await?;
+
+  let db = &ctx.data().postgres;
+  if MpServers::get_server(db, server.clone()).await?.is_none() {
+    ctx.reply(format!("**{server}** doesn't exist in database!")).await?;
+    return Ok(());
+  }
+
+  let period = period.unwrap_or(StatsPeriod::Week);
+  let since = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64 - period.days() * 86400;
+  let hours = PlayerSamples::hourly_since(db, &server, since).await?;
+
+  let Some(peak) = hours.iter().max_by_key(|h| h.peak_players) else {
+    ctx
+      .reply(format!("No player history for **{server}** in the last {}", period.label()))
+      .await?;
+    return Ok(());
+  };
+
+  let samples: i64 = hours.iter().map(|h| h.samples as i64).sum();
+  let average = hours.iter().map(|h| h.avg_players as f64 * h.samples as f64).sum::<f64>() / samples as f64;
+
+  let busiest = busiest_hours(&hours)
+    .iter()
+    .take(3)
+    .map(|(hour, avg)| format!("`{hour:02}:00` ─ {avg:.1} players"))
+    .collect::<Vec<_>>()
+    .join("\n");
+
+  let embed = CreateEmbed::new()
+    .color(BINARY_PROPERTIES.embed_colors.primary)
+    .title(format!("{server} | Last {}", period.label()))
+    .fields(vec![
+      ("Average", format!("{average:.1} players"), true),
+      ("Peak", format!("{} players on <t:{}:f>", peak.peak_players, peak.bucket), true),
+      ("Busiest hours (UTC)", busiest, false),
+    ])
+    .footer(CreateEmbedFooter::new(format!("Based on {samples} samples")));
+
+  ctx.send(CreateReply::default().embed(embed)).await?;
+
+  Ok(())
+}
+
+fn format_playtime(secs: i64) -> String {
+  let mins = secs / 60;
+  match (mins / 60, mins % 60) {
+    (0, m) => format!("{m} m"),
+    (h, 0) => format!("{h} h"),
+    (h, m) => format!("{h} h {m} m")
+  }
+}
+
+/// Shows who has played the most, on one server or all of them
+#[poise::command(slash_command)]
+async fn leaderboard(
+  ctx: super::PoiseContext<'_>,
+  #[description = "What server to rank players on, defaults to all of them"]
+  #[autocomplete = "ac_serverlist"]
+  server: Option<String>,
+  #[description = "How far back to look, defaults to a week"] period: Option<StatsPeriod>
+) -> Result<(), BotError> {
+  if !is_channel_allowed(ctx).await {
+    return Ok(());
+  }
+
+  ctx.defer().await?;
+
+  let period = period.unwrap_or(StatsPeriod::Week);
+  let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+  let since = now - period.days() * 86400;
+  let totals = PlayerSessions::leaderboard(&ctx.data().postgres, server.as_deref(), since, LEADERBOARD_SIZE).await?;
+  let scope = server.unwrap_or("All servers".to_string());
+
+  if totals.is_empty() {
+    ctx
+      .reply(format!("Nobody has played on **{scope}** in the last {}", period.label()))
+      .await?;
+    return Ok(());
+  }
+
+  let ranking = totals
+    .iter()
+    .enumerate()
+    .map(|(i, t)| format!("`{}.` **{}** ─ {} ({} sessions)", i + 1, t.name, format_playtime(t.playtime), t.sessions))
+    .collect::<Vec<_>>()
+    .join("\n");
+
+  let embed = CreateEmbed::new()
+    .color(BINARY_PROPERTIES.embed_colors.primary)
+    .title(format!("Playtime on {scope} | Last {}", period.label()))
+    .description(ranking);
+
+  ctx.send(CreateReply::default().embed(embed)).await?;
+
+  Ok(())
+}
+
+/// Shows a player's history across our servers
+#[poise::command(slash_command)]
+async fn player(
+  ctx: super::PoiseContext<'_>,
+  #[description = "In-game name of the player"] name: String
+) -> Result<(), BotError> {
+  if !is_channel_allowed(ctx).await {
+    return Ok(());
+  }
+
+  ctx.defer().await?;
+
+  let db = &ctx.data().postgres;
+  let totals = PlayerSessions::totals_for_player(db, &name).await?;
+  let recent = PlayerSessions::recent_for_player(db, &name, RECENT_SESSIONS).await?;
+
+  let Some(latest) = recent.first() else {
+    ctx.reply(format!("No sessions have been recorded for **{name}**")).await?;
+    return Ok(());
+  };
+
+  let servers = totals
+    .iter()
+    .map(|t| {
+      format!(
+        "**{}** ─ {} over {} sessions, last seen <t:{}:R>",
+        t.name,
+        format_playtime(t.playtime),
+        t.sessions,
+        t.last_seen
+      )
+    })
+    .collect::<Vec<_>>()
+    .join("\n");
+
+  let sessions = recent
+    .iter()
+    .map(|s| match s.duration {
+      Some(duration) => format!("<t:{}:f> on **{}** ─ {}", s.started_at, s.server_name, format_playtime(duration as i64)),
+      None => format!("Playing on **{}** since <t:{}:R>", s.server_name, s.started_at)
+    })
+    .collect::<Vec<_>>()
+    .join("\n");
+
+  let total: i64 = totals.iter().map(|t| t.playtime).sum();
+  let embed = CreateEmbed::new()
+    .color(BINARY_PROPERTIES.embed_colors.primary)
+    .title(latest.player_name.clone())
+    .fields(vec![("Servers", servers, false), ("Recent sessions", sessions, false)])
+    .footer(CreateEmbedFooter::new(format!("{} played in total", format_playtime(total))));
+
+  ctx.send(CreateReply::default().embed(embed)).await?;
+
+  Ok(())
+}
+
+/// Get a DM when a farmer joins or a server comes back online
+#[poise::command(slash_command, subcommands("notify_player", "notify_server", "notify_list", "notify_remove"))]
+async fn notify(_: super::PoiseContext<'_>) -> Result<(), BotError> { Ok(()) }
+
+/// Subscribe unless they're at the limit, re-subscribing to the same thing doesn't count towards it
+async fn subscribe(
+  ctx: super::PoiseContext<'_>,
+  kind: &str,
+  target: &str,
+  free_slots: Option<i32>
+) -> Result<bool, BotError> {
+  let db = &ctx.data().postgres;
+  let user_id = ctx.author().id.get() as i64;
+  let existing = MpSubscriptions::get_for_user(db, user_id).await?;
+
+  let lowered = target.to_lowercase();
+  let is_new = !existing.iter().any(|s| s.kind == kind && s.target.to_lowercase() == lowered);
+  if is_new && existing.len() >= MAX_SUBSCRIPTIONS {
+    ctx
+      .send(CreateReply::new().ephemeral(true).content(format!(
+        "You can only have {MAX_SUBSCRIPTIONS} subscriptions, remove one with `/mp notify remove` first."
+      )))
+      .await?;
+    return Ok(false);
+  }
+
+  MpSubscriptions::create(db, user_id, kind, target, free_slots).await?;
+  Ok(true)
+}
+
+/// Get a DM when the farmer joins any of our servers
+#[poise::command(slash_command, rename = "player")]
+async fn notify_player(
+  ctx: super::PoiseContext<'_>,
+  #[description = "In-game name of the farmer"] name: String
+) -> Result<(), BotError> {
+  if subscribe(ctx, MpSubscriptions::PLAYER, &name, None).await? {
+    ctx
+      .send(
+        CreateReply::new()
+          .ephemeral(true)
+          .content(format!("You'll get a DM when **{name}** joins one of our servers."))
+      )
+      .await?;
+  }
+
+  Ok(())
+}
+
+/// Get a DM when the server comes back online or is filling up
+#[poise::command(slash_command, rename = "server")]
+async fn notify_server(
+  ctx: super::PoiseContext<'_>,
+  #[description = "Server to watch"]
+  #[autocomplete = "ac_serverlist"]
+  server: String,
+  #[description = "Also notify when fewer than this many slots are free"] free_slots: Option<i32>
+) -> Result<(), BotError> {
+  if free_slots.is_some_and(|n| n < 1) {
+    ctx
+      .send(
+        CreateReply::new()
+          .ephemeral(true)
+          .content("The free slot threshold has to be at least 1.")
+      )
+      .await?;
+    return Ok(());
+  }
+
+  if MpServers::get_server(&ctx.data().postgres, server.clone()).await?.is_none() {
+    ctx
+      .send(
+        CreateReply::new()
+          .ephemeral(true)
+          .content(format!("**{server}** doesn't exist in database!"))
+      )
+      .await?;
+    return Ok(());
+  }
+
+  if subscribe(ctx, MpSubscriptions::SERVER, &server, free_slots).await? {
+    let slots = match free_slots {
+      Some(n) => format!(" or has fewer than {n} free slots"),
+      None => String::new()
+    };
+    ctx
+      .send(
+        CreateReply::new()
+          .ephemeral(true)
+          .content(format!("You'll get a DM when **{server}** comes back online{slots}."))
+      )
+      .await?;
+  }
+
+  Ok(())
+}
+
+/// List what you're getting notified about
+#[poise::command(slash_command, rename = "list")]
+async fn notify_list(ctx: super::PoiseContext<'_>) -> Result<(), BotError> {
+  let subs = MpSubscriptions::get_for_user(&ctx.data().postgres, ctx.author().id.get() as i64).await?;
+
+  let content = if subs.is_empty() {
+    "You're not subscribed to anything.".to_string()
+  } else {
+    subs
+      .iter()
+      .map(|s| match s.free_slots {
+        Some(n) => format!("- {} **{}** (under {n} free slots)", s.kind, s.target),
+        None => format!("- {} **{}**", s.kind, s.target)
+      })
+      .collect::<Vec<_>>()
+      .join("\n")
+  };
+
+  ctx.send(CreateReply::new().ephemeral(true).content(content)).await?;
+
+  Ok(())
+}
+
+/// Stop getting notified about a farmer or a server
+#[poise::command(slash_command, rename = "remove")]
+async fn notify_remove(
+  ctx: super::PoiseContext<'_>,
+  #[description = "What the subscription is for"] kind: SubscriptionKind,
+  #[description = "Name of the farmer or server"] name: String
+) -> Result<(), BotError> {
+  let removed = MpSubscriptions::delete(&ctx.data().postgres, ctx.author().id.get() as i64, kind.as_str(), &name).await?;
+
+  let content = if removed {
+    format!("You won't be notified about **{name}** anymore.")
+  } else {
+    format!("You weren't subscribed to **{name}**.")
+  };
+  ctx.send(CreateReply::new().ephemeral(true).content(content)).await?;
+
+  Ok(())
+}
+
 /// Fetches the given server's information like password, map and so forth
 #[poise::command(slash_command)]
 async fn details(
@@ -579,7 +966,7 @@ async fn tools_perm_check(ctx: super::PoiseContext<'_>) -> Result<bool, BotError
 }
 
 /// MP Manager tools for Monica
-#[poise::command(slash_command, check = "tools_perm_check", subcommands("list", "add", "delete", "update"))]
+#[poise::command(slash_command, check = "tools_perm_check", subcommands("list", "add", "delete", "update", "retention"))]
 async fn tools(_: super::PoiseContext<'_>) -> Result<(), BotError> { Ok(()) }
 
 /// List all available servers in the database
@@ -601,6 +988,7 @@ async fn list(ctx: super::PoiseContext<'_>) -> Result<(), BotError> {
     server_list.push(format!("  - Code: `{}`", server.code));
     server_list.push(format!("  - Password: `{}`", server.game_password));
     server_list.push(format!("  - Active: {active_flag}"));
+    server_list.push(format!("  - Join feed: {}", if server.join_feed { "Yes" } else { "No" }));
   }
 
   ctx.reply(server_list.join("\n")).await?;
@@ -676,9 +1064,10 @@ async fn update(
   name: String,
   #[description = "Server URL (DSS/CSG link)"] url: Option<String>,
   #[description = "Game password (If password is none, put a hyphen instead)"] password: Option<String>,
-  #[description = "Active status"] active: Option<bool>
+  #[description = "Active status"] active: Option<bool>,
+  #[description = "Post players joining and leaving into the server's thread"] join_feed: Option<bool>
 ) -> Result<(), BotError> {
-  if url.is_none() && password.is_none() && active.is_none() {
+  if url.is_none() && password.is_none() && active.is_none() && join_feed.is_none() {
     ctx.reply("Please provide atleast one field to update.").await?;
     return Ok(());
   }
@@ -706,8 +1095,9 @@ async fn update(
 
     let new_password = password.unwrap_or(server.game_password);
     let new_active = active.unwrap_or(server.is_active);
+    let new_join_feed = join_feed.unwrap_or(server.join_feed);
 
-    match MpServers::update_server(&ctx.data().postgres, name.clone(), new_active, ip, code, new_password).await {
+    match MpServers::update_server(&ctx.data().postgres, name.clone(), new_active, ip, code, new_password, new_join_feed).await {
       Ok(_) => {
         ctx.data().redis.del(TASK_NAME).await?;
         ctx.reply(format!("**{name}**'s information successfully updated!")).await?;
@@ -720,3 +1110,63 @@ async fn update(
 
   Ok(())
 }
+
+/// View or change how many days of player history are kept
+#[poise::command(slash_command)]
+async fn retention(
+  ctx: super::PoiseContext<'_>,
+  #[description = "Days to keep every sample for"] samples: Option<i32>,
+  #[description = "Days to keep the hourly averages for"] hourly: Option<i32>,
+  #[description = "Days to keep the daily averages for"] daily: Option<i32>
+) -> Result<(), BotError> {
+  let db = &ctx.data().postgres;
+  let current = Settings::get_player_retention(db).await?;
+
+  if samples.is_none() && hourly.is_none() && daily.is_none() {
+    ctx
+      .reply(format!(
+        "Player history is kept for:\n- Samples: **{} days**\n- Hourly: **{} days**\n- Daily: **{} days**",
+        current.samples, current.hourly, current.daily
+      ))
+      .await?;
+    return Ok(());
+  }
+
+  let retention = PlayerRetention {
+    samples: samples.unwrap_or(current.samples),
+    hourly:  hourly.unwrap_or(current.hourly),
+    daily:   daily.unwrap_or(current.daily)
+  };
+
+  if retention.samples < 1 {
+    ctx.reply("Samples have to be kept for at least a day so they can be rolled up.").await?;
+    return Ok(());
+  }
+
+  if retention.hourly < MIN_HOURLY_RETENTION_DAYS {
+    ctx
+      .reply(format!(
+        "Hourly averages have to be kept for at least {MIN_HOURLY_RETENTION_DAYS} days for `/mp stats` to work."
+      ))
+      .await?;
+    return Ok(());
+  }
+
+  // Each resolution is built from the finer one, it can't run out before it
+  if retention.samples > retention.hourly || retention.hourly > retention.daily {
+    ctx
+      .reply("Retention can't be shorter for the hourly and daily averages than for what they're built from.")
+      .await?;
+    return Ok(());
+  }
+
+  Settings::update_player_retention(db, retention).await?;
+  ctx
+    .reply(format!(
+      "Player history will now be kept for **{}**, **{}** and **{}** days (samples, hourly, daily)",
+      retention.samples, retention.hourly, retention.daily
+    ))
+    .await?;
+
+  Ok(())
+}
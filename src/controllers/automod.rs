@@ -1,3 +1,19 @@
+mod attachments;
+pub use attachments::dhash;
+
+mod backend;
+
+mod domains;
+pub use domains::{
+  MaliciousDomains,
+  parse_entry as parse_domain
+};
+
+#[cfg(test)]
+mod tests;
+
+mod urls;
+
 use crate::{
   BotData,
   BotError,
@@ -10,51 +26,71 @@ use crate::{
   controllers::{
     cache::RedisController,
     sql::{
+      AutomodAllowlist,
+      AutomodEvents,
+      AutomodExemptions,
+      AutomodPartners,
+      AutomodPolicies,
+      AutomodSources,
       ProhibitedUrls,
-      ProhibitedWords,
-      Sanctions
+      Sanctions,
+      ScamImages
     }
   },
   internals::{
     config::BINARY_PROPERTIES,
+    nickname,
     utils::{
       format_duration,
       token_path
-    }
+    },
+    word_filter::WordFilter
   }
 };
 
 use {
-  dashmap::DashMap,
   lazy_static::lazy_static,
   poise::serenity_prelude::{
+    AttachmentId,
+    ButtonStyle,
     CacheHttp,
+    Channel,
+    ChannelId,
     Context,
+    CreateActionRow,
+    CreateButton,
     CreateEmbed,
     CreateMessage,
+    EditMember,
+    Embed,
+    GenericChannelId,
+    GuildId,
+    Http,
     Member,
     Mentionable,
     Message,
+    MessageId,
     Timestamp,
+    User,
     UserId
   },
   regex::Regex,
-  reqwest::{
-    Client,
-    Url
-  },
+  reqwest::Client,
   serde::{
     Deserialize,
     Serialize
   },
-  smallvec::SmallVec,
+  similar::TextDiff,
   sqlx::PgPool,
   std::{
     borrow::Cow,
+    collections::HashSet,
+    str::FromStr,
     sync::{
       Arc,
+      RwLock as StdRwLock,
       atomic::{
-        AtomicU32,
+        AtomicI64,
         Ordering::SeqCst
       }
     },
@@ -70,177 +106,850 @@ use {
   }
 };
 
+use self::{
+  backend::{
+    AutomodBackend,
+    LiveBackend
+  },
+  domains::{
+    DomainSet,
+    is_github_source,
+    parse_blocklist
+  }
+};
+
 const MD_KEY_MAIN: &str = "MaliciousDomains";
 const MD_KEY_LU: &str = "MaliciousDomains:LastUpdate";
-const MD_BLOCKLIST: [&str; 4] = [
-  "https://raw.githubusercontent.com/Discord-AntiScam/scam-links/main/list.txt",
-  "https://raw.githubusercontent.com/mitchellkrogza/Phishing.Database/master/phishing-links-NEW-today.txt",
-  "https://raw.githubusercontent.com/RedPanda4552/PandaPhishLists/main/seen-on-discord.txt",
-  "https://raw.githubusercontent.com/nwerosama/FishDB/main/domains.txt"
-];
+const INVITE_KEY: &str = "Automod:Invite";
+/// Invites can be deleted or repointed, so resolved guilds don't stick around forever
+const INVITE_CACHE_SECS: i64 = 86400;
+/// Cached in place of a guild for invites that didn't resolve, so a spammed dead invite doesn't hit the API every time
+const INVITE_UNRESOLVED: &str = "unresolved";
+/// Kept short since a failed lookup may have just been Discord having a moment
+const INVITE_UNRESOLVED_SECS: i64 = 300;
+const PROFILE_KEY: &str = "Automod:Profile";
+/// Warning counter of a user towards a policy, followed by `:{user_id}:{policy_type}`
+const WARNINGS_KEY: &str = "Automod:Warnings";
+/// Windows of a user's recent activity for the rate-based policies, followed by `:{user_id}`
+const MESSAGES_KEY: &str = "Automod:Messages";
+const MENTIONS_KEY: &str = "Automod:Mentions";
+const RECENT_KEY: &str = "Automod:Recent";
+/// How long a flagged name is remembered for, so member updates don't sanction it again
+const PROFILE_CACHE_SECS: i64 = 604800;
+/// Custom ID prefix of the pardon button on the violation logs, followed by `:{case_id}:{policy_type}`
+pub const PARDON_BUTTON: &str = "automod-pardon";
 
 lazy_static! {
-  static ref URL_REGEX: Regex = Regex::new(r"(?i)(?:https?://)?(?:www\.)?([a-zA-Z0-9][a-zA-Z0-9-]*(?:\.[a-zA-Z0-9-]+)+)").unwrap();
-  static ref INVITE_REGEX: Regex = Regex::new(r"(?i)discord(?:\.gg|(?:app)?\.com/invite)/[\w-]+").unwrap();
-  static ref MASKED_URL_REGEX: Regex = Regex::new(r"\[.*?\]\(<?(https?://[^>]+)>?\)").unwrap();
+  static ref INVITE_REGEX: Regex = Regex::new(r"(?i)discord(?:\.gg|(?:app)?\.com/invite)/([\w-]+)").unwrap();
+  static ref LINK_REGEX: Regex = Regex::new(r"(?i)\b(?:https?://|www\.)\S+").unwrap();
   static ref REQWEST_CLIENT: Client = Client::new();
 }
 
 // Rule configuration
 #[derive(Debug, Clone)]
 pub struct AutomodPolicy {
-  pub enabled:        bool,
-  pub policy_type:    AutomodPolicyType,
-  pub action:         ActionType,
-  pub reason:         String,
+  pub enabled:           bool,
+  pub policy_type:       AutomodPolicyType,
+  pub action:            ActionType,
+  pub reason:            String,
   /// Number of warns before policy's action is triggered
-  pub warn_threshold: u32,
+  pub warn_threshold:    u32,
   /// Duration in seconds
-  pub mute_duration:  Option<i64>
+  pub mute_duration:     Option<i64>,
+  /// Messages, mentions or channels needed within the window, only used by rate-based policies
+  pub trigger_threshold: u32,
+  /// Window in seconds for `trigger_threshold`
+  pub window_secs:       u32,
+  /// Warnings are forgotten after this many seconds without a new one
+  pub decay_secs:        u32,
+  /// Accounts younger than this many days are restricted, only used by `NewAccount`
+  pub account_age_days:  u32,
+  /// Members who joined less than this many seconds ago are restricted, only used by `NewAccount`
+  pub member_age_secs:   u32,
+  /// Only delete the message (or reset the name), the user is never warned or sanctioned
+  pub delete_only:       bool,
+  /// Log what would've happened instead of enforcing it
+  pub shadow:            bool
 }
 
-#[derive(Debug, Clone, Eq, Hash, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Eq, Hash, PartialEq, Serialize, Deserialize, poise::ChoiceParameter)]
 pub enum AutomodPolicyType {
   AntiSpam,
   InviteLinks,
   ProhibitedWords,
   MaliciousLinks, // For phishing links
-  ProhibitedUrls
+  ProhibitedUrls,
+  MassMention,
+  DuplicateMessage, // Same message across channels
+  Attachments,      // Dangerous files and known scam images
+  NewAccount,       // Links, files and mentions from fresh accounts or members
+  Profile           // Usernames, display names and nicknames
 }
 
-// Spam tracking
-#[derive(Debug, Default, Serialize, Deserialize)]
-struct UserMessageStats {
-  messages:        SmallVec<[i64; 5]>,
-  policy_warnings: DashMap<AutomodPolicyType, (AtomicU32, i64)>
+impl AutomodPolicyType {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      Self::AntiSpam => "AntiSpam",
+      Self::InviteLinks => "InviteLinks",
+      Self::ProhibitedWords => "ProhibitedWords",
+      Self::MaliciousLinks => "MaliciousLinks",
+      Self::ProhibitedUrls => "ProhibitedUrls",
+      Self::MassMention => "MassMention",
+      Self::DuplicateMessage => "DuplicateMessage",
+      Self::Attachments => "Attachments",
+      Self::NewAccount => "NewAccount",
+      Self::Profile => "Profile"
+    }
+  }
+
+  /// Whether the policy counts things over a window, rather than checking a single message
+  pub fn is_rate_based(&self) -> bool { matches!(self, Self::AntiSpam | Self::MassMention | Self::DuplicateMessage) }
 }
 
-impl UserMessageStats {
-  fn increment_warnings(
-    &self,
-    policy_type: &AutomodPolicyType,
-    timestamp: i64
-  ) -> u32 {
-    let mut entry = self
-      .policy_warnings
-      .entry(policy_type.clone())
-      .or_insert_with(|| (AtomicU32::new(0), timestamp));
-    entry.1 = timestamp;
-    entry.0.fetch_add(1, SeqCst) + 1
+impl FromStr for AutomodPolicyType {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "AntiSpam" => Ok(Self::AntiSpam),
+      "InviteLinks" => Ok(Self::InviteLinks),
+      "ProhibitedWords" => Ok(Self::ProhibitedWords),
+      "MaliciousLinks" => Ok(Self::MaliciousLinks),
+      "ProhibitedUrls" => Ok(Self::ProhibitedUrls),
+      "MassMention" => Ok(Self::MassMention),
+      "DuplicateMessage" => Ok(Self::DuplicateMessage),
+      "Attachments" => Ok(Self::Attachments),
+      "NewAccount" => Ok(Self::NewAccount),
+      "Profile" => Ok(Self::Profile),
+      _ => Err(format!("`{s}` is not a valid policy type"))
+    }
   }
+}
 
-  fn reset_warnings(
-    &self,
-    policy_type: &AutomodPolicyType
-  ) {
-    if let Some(entry) = self.policy_warnings.get(policy_type) {
-      entry.0.store(0, SeqCst);
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExemptionTarget {
+  Role,
+  Channel,
+  Category
+}
+
+impl ExemptionTarget {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      Self::Role => "Role",
+      Self::Channel => "Channel",
+      Self::Category => "Category"
     }
   }
+}
+
+impl FromStr for ExemptionTarget {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "Role" => Ok(Self::Role),
+      "Channel" => Ok(Self::Channel),
+      "Category" => Ok(Self::Category),
+      _ => Err(format!("`{s}` is not a valid exemption target"))
+    }
+  }
+}
 
-  fn check_and_reset_warns(
+#[derive(Debug, Clone)]
+pub struct AutomodExemption {
+  /// `None` exempts from every policy
+  pub policy_type: Option<AutomodPolicyType>,
+  pub target:      ExemptionTarget,
+  pub target_id:   u64
+}
+
+impl AutomodExemption {
+  fn from_row(row: AutomodExemptions) -> Result<Self, String> {
+    Ok(Self {
+      policy_type: match row.policy_type.as_str() {
+        "All" => None,
+        p => Some(AutomodPolicyType::from_str(p)?)
+      },
+      target:      ExemptionTarget::from_str(&row.target_type)?,
+      target_id:   row.target_id as u64
+    })
+  }
+
+  pub fn to_row(&self) -> AutomodExemptions {
+    AutomodExemptions {
+      policy_type: self.policy_type.as_ref().map_or("All", |p| p.as_str()).to_string(),
+      target_type: self.target.as_str().to_string(),
+      target_id:   self.target_id as i64
+    }
+  }
+
+  pub async fn load_all(db: &PgPool) -> Result<Vec<Self>, BotError> {
+    Ok(
+      AutomodExemptions::get_exemptions(db)
+        .await?
+        .into_iter()
+        .filter_map(|row| {
+          Self::from_row(row)
+            .map_err(|e| eprintln!("[automod::load_exemptions] Skipping malformed exemption: {e}"))
+            .ok()
+        })
+        .collect()
+    )
+  }
+
+  fn covers(
     &self,
     policy_type: &AutomodPolicyType,
-    current_ts: i64,
-    reset_interval: i64
-  ) {
-    if let Some(entry) = self.policy_warnings.get(policy_type) {
-      if current_ts - entry.1 >= reset_interval {
-        self.reset_warnings(policy_type);
-      }
+    scope: &MessageScope
+  ) -> bool {
+    if self.policy_type.as_ref().is_some_and(|p| p != policy_type) {
+      return false;
+    }
+
+    match self.target {
+      ExemptionTarget::Role => scope.roles.contains(&self.target_id),
+      ExemptionTarget::Channel => scope.channels.contains(&self.target_id),
+      ExemptionTarget::Category => scope.category == Some(self.target_id)
     }
   }
 }
 
+/// What tripped a policy and why
+pub struct Detection {
+  /// Bare word, domain, invite or file that matched, if there's one to point at
+  pub indicator: Option<String>,
+  pub reason:    String
+}
+
+impl Detection {
+  fn new(
+    indicator: impl Into<String>,
+    reason: String
+  ) -> Self {
+    Self {
+      indicator: Some(indicator.into()),
+      reason
+    }
+  }
+
+  fn rate(reason: String) -> Self { Self { indicator: None, reason } }
+}
+
+/// Where the message was sent and who sent it, only looked up once a violation has been found
+struct MessageScope {
+  roles:    Vec<u64>,
+  /// The channel itself, plus the parent channel if it's a thread
+  channels: Vec<u64>,
+  category: Option<u64>
+}
+
+/// Who tripped a policy and where, either through a message or their profile
+struct Subject<'a> {
+  user:      &'a User,
+  guild_id:  GuildId,
+  /// `None` when the profile is the one in violation
+  message:   Option<&'a Message>,
+  timestamp: Timestamp
+}
+
+impl<'a> Subject<'a> {
+  fn message(msg: &'a Message) -> Self {
+    Self {
+      user:      &msg.author,
+      guild_id:  msg.guild_id.expect("Expected message to be in guild"),
+      message:   Some(msg),
+      timestamp: msg.timestamp
+    }
+  }
+
+  fn profile(member: &'a Member) -> Self {
+    Self {
+      user:      &member.user,
+      guild_id:  member.guild_id,
+      message:   None,
+      timestamp: Timestamp::now()
+    }
+  }
+}
+
+/// The parts of a message the checks look at, so detection doesn't need a live [Message]
+struct Incoming<'a> {
+  author_id:        u64,
+  /// Unix timestamp of when the author's account was created
+  account_created:  i64,
+  /// Unix timestamp of when the author joined, edits don't come with the member
+  joined_at:        Option<i64>,
+  channel_id:       u64,
+  message_id:       u64,
+  content:          &'a str,
+  timestamp:        i64,
+  /// Users and roles pinged, the author pinging themselves doesn't count
+  mentions:         u32,
+  mention_everyone: bool,
+  attachments:      Vec<IncomingFile<'a>>,
+  /// Text of the embeds and forwarded messages, only looked at for links
+  embedded:         Vec<&'a str>
+}
+
+struct IncomingFile<'a> {
+  filename: &'a str,
+  size:     u32,
+  url:      &'a str
+}
+
+impl<'a> From<&'a Message> for Incoming<'a> {
+  fn from(msg: &'a Message) -> Self {
+    Self {
+      author_id:        msg.author.id.get(),
+      account_created:  msg.author.id.created_at().unix_timestamp(),
+      joined_at:        msg.member.as_ref().and_then(|m| m.joined_at).map(|j| j.unix_timestamp()),
+      channel_id:       msg.channel_id.get(),
+      message_id:       msg.id.get(),
+      content:          &msg.content,
+      timestamp:        msg.timestamp.unix_timestamp(),
+      mentions:         (msg.mentions.iter().filter(|u| u.id != msg.author.id).count() + msg.mention_roles.len()) as u32,
+      mention_everyone: msg.mention_everyone,
+      attachments:      msg
+        .attachments
+        .iter()
+        .map(|a| IncomingFile {
+          filename: &a.filename,
+          size:     a.size,
+          url:      &a.url
+        })
+        .collect(),
+      embedded:         msg
+        .message_snapshots
+        .iter()
+        .map(|s| &*s.content)
+        .chain(
+          msg
+            .embeds
+            .iter()
+            .chain(msg.message_snapshots.iter().flat_map(|s| s.embeds.iter()))
+            .flat_map(embed_text)
+        )
+        .filter(|t| !t.is_empty())
+        .collect()
+    }
+  }
+}
+
+impl Incoming<'_> {
+  /// Message content along with whatever is embedded in it, for the link checks
+  fn link_text(&self) -> Cow<'_, str> {
+    if self.embedded.is_empty() {
+      Cow::Borrowed(self.content)
+    } else {
+      Cow::Owned(
+        std::iter::once(self.content)
+          .chain(self.embedded.iter().copied())
+          .collect::<Vec<_>>()
+          .join("\n")
+      )
+    }
+  }
+}
+
+/// Parts of an embed that can carry a link
+fn embed_text(embed: &Embed) -> impl Iterator<Item = &str> {
+  [
+    embed.url.as_deref(),
+    embed.title.as_deref(),
+    embed.description.as_deref(),
+    embed.author.as_ref().and_then(|a| a.url.as_deref())
+  ]
+  .into_iter()
+  .flatten()
+  .chain(embed.fields.iter().map(|f| &*f.value))
+}
+
+/// Messages shorter than this are too generic to be treated as duplicates
+const MIN_DUPLICATE_LEN: usize = 10;
+/// How alike two messages need to be to count as the same one
+const DUPLICATE_SIMILARITY: f32 = 0.9;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct RecentMessage {
+  timestamp:  i64,
+  channel_id: u64,
+  message_id: u64,
+  content:    String
+}
+
+/// Outcome of the rate-based policies for a single message
+#[derive(Default)]
+struct Activity {
+  spam:         bool,
+  mass_mention: bool,
+  duplicate:    bool
+}
+
+fn warnings_key(
+  user_id: u64,
+  policy_type: &AutomodPolicyType
+) -> String {
+  format!("{WARNINGS_KEY}:{user_id}:{}", policy_type.as_str())
+}
+
+fn profile_key(user_id: u64) -> String { format!("{PROFILE_KEY}:{user_id}") }
+
+fn duplicate_fingerprint(content: &str) -> String {
+  content
+    .to_lowercase()
+    .split_whitespace()
+    .collect::<Vec<_>>()
+    .join(" ")
+    .chars()
+    .take(300)
+    .collect()
+}
+
+fn is_duplicate(
+  a: &str,
+  b: &str
+) -> bool {
+  a == b || TextDiff::from_chars(a, b).ratio() >= DUPLICATE_SIMILARITY
+}
+
+/// Shared across all messages, word and URL lists are
+/// compiled once and swapped out when they get modified
 pub struct Automoderator {
   policies: Arc<RwLock<Vec<AutomodPolicy>>>,
-  pw_list:  Vec<Regex>,
-  pu_list:  Vec<String>,
-  redis:    Arc<RedisController>
+  pw_list:  StdRwLock<Arc<WordFilter>>,
+  pu_list:  StdRwLock<Arc<Vec<String>>>,
+  md_list:  StdRwLock<Arc<DomainSet>>,
+  md_allow: StdRwLock<Arc<DomainSet>>,
+  exempts:  StdRwLock<Arc<Vec<AutomodExemption>>>,
+  /// Guilds that invite links are allowed to point to, besides the home guild
+  partners: StdRwLock<Arc<HashSet<u64>>>,
+  /// Perceptual hashes of known scam images and their labels
+  scam_img: StdRwLock<Arc<Vec<(u64, String)>>>,
+  /// Timestamp of the Redis snapshot currently held in `md_list`
+  md_epoch: AtomicI64,
+  /// Redis and Discord when running, memory in the tests
+  backend:  Arc<dyn AutomodBackend>
 }
 
 impl AutomodPolicy {
   pub fn anti_spam() -> Self {
     Self {
-      enabled:        true,
-      policy_type:    AutomodPolicyType::AntiSpam,
-      action:         ActionType::Mute,
-      reason:         "Spam detection".to_string(),
-      warn_threshold: 3,
-      mute_duration:  Some(3600) // 1 hour
+      enabled:           true,
+      policy_type:       AutomodPolicyType::AntiSpam,
+      action:            ActionType::Mute,
+      reason:            "Spam detection".to_string(),
+      warn_threshold:    3,
+      mute_duration:     Some(3600), // 1 hour
+      trigger_threshold: 4,
+      window_secs:       5,
+      decay_secs:        300,
+      account_age_days:  0,
+      member_age_secs:   0,
+      delete_only:       false,
+      shadow:            false
     }
   }
 
   pub fn prohibited_words() -> Self {
     Self {
-      enabled:        true,
-      policy_type:    AutomodPolicyType::ProhibitedWords,
-      action:         ActionType::Mute,
-      reason:         "Use of prohibited words".to_string(),
-      warn_threshold: 2,
-      mute_duration:  Some(1800) // 30 minutes
+      enabled:           true,
+      policy_type:       AutomodPolicyType::ProhibitedWords,
+      action:            ActionType::Mute,
+      reason:            "Use of prohibited words".to_string(),
+      warn_threshold:    2,
+      mute_duration:     Some(1800), // 30 minutes
+      trigger_threshold: 0,
+      window_secs:       0,
+      decay_secs:        300,
+      account_age_days:  0,
+      member_age_secs:   0,
+      delete_only:       false,
+      shadow:            false
     }
   }
 
   pub fn invite_links() -> Self {
     Self {
-      enabled:        true,
-      policy_type:    AutomodPolicyType::InviteLinks,
-      action:         ActionType::Ban,
-      reason:         "Posting invite link".to_string(),
-      warn_threshold: 2,
-      mute_duration:  None
+      enabled:           true,
+      policy_type:       AutomodPolicyType::InviteLinks,
+      action:            ActionType::Ban,
+      reason:            "Posting invite link".to_string(),
+      warn_threshold:    2,
+      mute_duration:     None,
+      trigger_threshold: 0,
+      window_secs:       0,
+      decay_secs:        300,
+      account_age_days:  0,
+      member_age_secs:   0,
+      delete_only:       false,
+      shadow:            false
     }
   }
 
   pub fn malicious_links() -> Self {
     Self {
-      enabled:        true,
-      policy_type:    AutomodPolicyType::MaliciousLinks,
-      action:         ActionType::Ban,
-      reason:         "Posting a malicious link".to_string(),
-      warn_threshold: 2,
-      mute_duration:  None
+      enabled:           true,
+      policy_type:       AutomodPolicyType::MaliciousLinks,
+      action:            ActionType::Ban,
+      reason:            "Posting a malicious link".to_string(),
+      warn_threshold:    2,
+      mute_duration:     None,
+      trigger_threshold: 0,
+      window_secs:       0,
+      decay_secs:        300,
+      account_age_days:  0,
+      member_age_secs:   0,
+      delete_only:       false,
+      shadow:            false
     }
   }
 
   pub fn prohibited_urls() -> Self {
     Self {
-      enabled:        true,
-      policy_type:    AutomodPolicyType::ProhibitedUrls,
-      action:         ActionType::Mute,
-      reason:         "Posting a banned link".to_string(),
-      warn_threshold: 2,
-      mute_duration:  Some(1800) // 30 minutes
+      enabled:           true,
+      policy_type:       AutomodPolicyType::ProhibitedUrls,
+      action:            ActionType::Mute,
+      reason:            "Posting a banned link".to_string(),
+      warn_threshold:    2,
+      mute_duration:     Some(1800), // 30 minutes
+      trigger_threshold: 0,
+      window_secs:       0,
+      decay_secs:        300,
+      account_age_days:  0,
+      member_age_secs:   0,
+      delete_only:       false,
+      shadow:            false
+    }
+  }
+
+  pub fn mass_mention() -> Self {
+    Self {
+      enabled:           true,
+      policy_type:       AutomodPolicyType::MassMention,
+      action:            ActionType::Mute,
+      reason:            "Mass mentioning".to_string(),
+      warn_threshold:    2,
+      mute_duration:     Some(3600), // 1 hour
+      trigger_threshold: 6,
+      window_secs:       30,
+      decay_secs:        300,
+      account_age_days:  0,
+      member_age_secs:   0,
+      delete_only:       false,
+      shadow:            false
+    }
+  }
+
+  pub fn duplicate_message() -> Self {
+    Self {
+      enabled:           true,
+      policy_type:       AutomodPolicyType::DuplicateMessage,
+      action:            ActionType::Mute,
+      reason:            "Posting the same message across channels".to_string(),
+      warn_threshold:    1,
+      mute_duration:     Some(86400), // 1 day
+      trigger_threshold: 3,
+      window_secs:       60,
+      decay_secs:        300,
+      account_age_days:  0,
+      member_age_secs:   0,
+      delete_only:       false,
+      shadow:            false
+    }
+  }
+
+  pub fn attachments() -> Self {
+    Self {
+      enabled:           true,
+      policy_type:       AutomodPolicyType::Attachments,
+      action:            ActionType::Mute,
+      reason:            "Posting a dangerous file or scam image".to_string(),
+      warn_threshold:    1,
+      mute_duration:     Some(86400), // 1 day
+      trigger_threshold: 0,
+      window_secs:       0,
+      decay_secs:        300,
+      account_age_days:  0,
+      member_age_secs:   0,
+      delete_only:       false,
+      shadow:            false
+    }
+  }
+
+  pub fn new_account() -> Self {
+    Self {
+      enabled:           true,
+      policy_type:       AutomodPolicyType::NewAccount,
+      action:            ActionType::Warn,
+      reason:            "Posting links, files or mentions from a new account".to_string(),
+      warn_threshold:    0,
+      mute_duration:     None,
+      trigger_threshold: 0,
+      window_secs:       0,
+      decay_secs:        300,
+      account_age_days:  7,
+      member_age_secs:   1800, // 30 minutes
+      delete_only:       true, // only delete the message
+      shadow:            false
+    }
+  }
+
+  pub fn profile() -> Self {
+    Self {
+      enabled:           true,
+      policy_type:       AutomodPolicyType::Profile,
+      action:            ActionType::Mute,
+      reason:            "Prohibited word or malicious link in profile".to_string(),
+      warn_threshold:    1,
+      mute_duration:     Some(86400), // 1 day
+      trigger_threshold: 0,
+      window_secs:       0,
+      decay_secs:        300,
+      account_age_days:  0,
+      member_age_secs:   0,
+      delete_only:       false,
+      shadow:            true // names are noisier than messages, watch it before enforcing
+    }
+  }
+
+  /// Built-in policies, used to seed the database
+  pub fn defaults() -> Vec<Self> {
+    vec![
+      Self::anti_spam(),
+      Self::prohibited_words(),
+      Self::invite_links(),
+      Self::malicious_links(),
+      Self::prohibited_urls(),
+      Self::mass_mention(),
+      Self::duplicate_message(),
+      Self::attachments(),
+      Self::new_account(),
+      Self::profile(),
+    ]
+  }
+
+  /// Whether reaching this many warnings sanctions the user
+  pub fn escalates_at(
+    &self,
+    warnings: i64
+  ) -> bool {
+    // A threshold of 0 sanctions on the first violation
+    !self.delete_only && warnings >= self.warn_threshold as i64
+  }
+
+  fn default_for(policy_type: &AutomodPolicyType) -> Self {
+    Self::defaults()
+      .into_iter()
+      .find(|p| p.policy_type == *policy_type)
+      .expect("Every policy type has a default")
+  }
+
+  fn from_row(row: AutomodPolicies) -> Result<Self, String> {
+    let policy_type = AutomodPolicyType::from_str(&row.policy_type)?;
+    // Rows from before the rate-based columns existed fall back to the built-in values
+    let default = Self::default_for(&policy_type);
+
+    Ok(Self {
+      enabled: row.enabled,
+      policy_type,
+      action: ActionType::from_str(&row.action)?,
+      reason: row.reason,
+      warn_threshold: row.warn_threshold.max(0) as u32,
+      mute_duration: row.mute_duration,
+      trigger_threshold: row.trigger_threshold.map_or(default.trigger_threshold, |t| t.max(0) as u32),
+      window_secs: row.window_secs.map_or(default.window_secs, |w| w.max(0) as u32),
+      decay_secs: row.decay_secs.map_or(default.decay_secs, |d| d.max(0) as u32),
+      account_age_days: row.account_age_days.map_or(default.account_age_days, |d| d.max(0) as u32),
+      member_age_secs: row.member_age_secs.map_or(default.member_age_secs, |m| m.max(0) as u32),
+      delete_only: row.delete_only,
+      shadow: row.shadow
+    })
+  }
+
+  pub fn to_row(&self) -> AutomodPolicies {
+    AutomodPolicies {
+      policy_type:       self.policy_type.as_str().to_string(),
+      enabled:           self.enabled,
+      action:            self.action.to_string(),
+      reason:            self.reason.clone(),
+      warn_threshold:    self.warn_threshold as i32,
+      mute_duration:     self.mute_duration,
+      trigger_threshold: Some(self.trigger_threshold as i32),
+      window_secs:       Some(self.window_secs as i32),
+      decay_secs:        Some(self.decay_secs as i32),
+      account_age_days:  Some(self.account_age_days as i32),
+      member_age_secs:   Some(self.member_age_secs as i32),
+      delete_only:       self.delete_only,
+      shadow:            self.shadow
     }
   }
+
+  /// Load the policies from the database, seeding any policy that doesn't have a row yet
+  pub async fn load_all(db: &PgPool) -> Result<Vec<Self>, BotError> {
+    let mut policies: Vec<Self> = AutomodPolicies::get_policies(db)
+      .await?
+      .into_iter()
+      .filter_map(|row| {
+        Self::from_row(row)
+          .map_err(|e| eprintln!("[automod::load_policies] Skipping malformed policy: {e}"))
+          .ok()
+      })
+      .collect();
+
+    for default in Self::defaults() {
+      if !policies.iter().any(|p| p.policy_type == default.policy_type) {
+        default.to_row().upsert(db).await?;
+        policies.push(default);
+      }
+    }
+
+    Ok(policies)
+  }
 }
 
 impl Automoderator {
   pub async fn new(
     db: &PgPool,
-    redis: Arc<RedisController>
+    redis: Arc<RedisController>,
+    http: Arc<Http>
   ) -> Result<Self, BotError> {
     Ok(Self {
-      policies: Arc::new(RwLock::new(vec![
-        AutomodPolicy::anti_spam(),
-        AutomodPolicy::prohibited_words(),
-        AutomodPolicy::invite_links(),
-        AutomodPolicy::malicious_links(),
-        AutomodPolicy::prohibited_urls(),
-      ])),
-      pw_list: Self::load_prohibited_words(db).await?,
-      pu_list: Self::load_prohibited_urls(db).await?,
-      redis
+      policies: Arc::new(RwLock::new(AutomodPolicy::load_all(db).await?)),
+      pw_list:  StdRwLock::new(Arc::new(WordFilter::load(db).await?)),
+      pu_list:  StdRwLock::new(Arc::new(Self::load_prohibited_urls(db).await?)),
+      md_list:  StdRwLock::new(Arc::new(DomainSet::default())),
+      md_allow: StdRwLock::new(Arc::new(Self::load_allowlist(db).await?)),
+      exempts:  StdRwLock::new(Arc::new(AutomodExemption::load_all(db).await?)),
+      partners: StdRwLock::new(Arc::new(Self::load_partners(db).await?)),
+      scam_img: StdRwLock::new(Arc::new(Self::load_scam_images(db).await?)),
+      md_epoch: AtomicI64::new(0),
+      backend:  Arc::new(LiveBackend::new(redis, http))
     })
   }
 
-  fn staff_check(
+  /// Reload the policies after they've been changed
+  pub async fn reload_policies(
     &self,
-    member: &Member
-  ) -> bool {
-    let staff_roles = [468842789053136897, 468841295150972929];
-    member.roles.iter().any(|r| staff_roles.contains(&r.get()))
+    db: &PgPool
+  ) -> Result<(), BotError> {
+    let policies = AutomodPolicy::load_all(db).await?;
+    *self.policies.write().await = policies;
+    Ok(())
+  }
+
+  /// The compiled prohibited words, shared with the nickname checks so they don't have to build their own
+  pub fn word_filter(&self) -> Arc<WordFilter> { Arc::clone(&self.pw_list.read().unwrap()) }
+
+  /// Recompile the prohibited words after the list or its allowlist has been changed
+  pub async fn reload_words(
+    &self,
+    db: &PgPool
+  ) -> Result<(), BotError> {
+    let words = WordFilter::load(db).await?;
+    *self.pw_list.write().unwrap() = Arc::new(words);
+    Ok(())
+  }
+
+  /// Reload the prohibited URLs after the list has been changed
+  pub async fn reload_urls(
+    &self,
+    db: &PgPool
+  ) -> Result<(), BotError> {
+    let urls = Self::load_prohibited_urls(db).await?;
+    *self.pu_list.write().unwrap() = Arc::new(urls);
+    Ok(())
+  }
+
+  /// Reload the malicious domain allowlist after it has been changed
+  pub async fn reload_allowlist(
+    &self,
+    db: &PgPool
+  ) -> Result<(), BotError> {
+    let allowlist = Self::load_allowlist(db).await?;
+    *self.md_allow.write().unwrap() = Arc::new(allowlist);
+    Ok(())
+  }
+
+  /// Reload the exemptions after they've been changed
+  pub async fn reload_exemptions(
+    &self,
+    db: &PgPool
+  ) -> Result<(), BotError> {
+    let exemptions = AutomodExemption::load_all(db).await?;
+    *self.exempts.write().unwrap() = Arc::new(exemptions);
+    Ok(())
+  }
+
+  /// Reload the partner guilds after they've been changed
+  pub async fn reload_partners(
+    &self,
+    db: &PgPool
+  ) -> Result<(), BotError> {
+    let partners = Self::load_partners(db).await?;
+    *self.partners.write().unwrap() = Arc::new(partners);
+    Ok(())
+  }
+
+  /// Reload the scam image hashes after they've been changed
+  pub async fn reload_scam_images(
+    &self,
+    db: &PgPool
+  ) -> Result<(), BotError> {
+    let images = Self::load_scam_images(db).await?;
+    *self.scam_img.write().unwrap() = Arc::new(images);
+    Ok(())
+  }
+
+  /// Thread parent and category of the message's channel, if the guild and its channels are cached.<br>
+  /// The cache guard can't be held across an await, so this stays synchronous.
+  fn cached_location(
+    ctx: &Context,
+    msg: &Message
+  ) -> Option<(Option<u64>, Option<u64>)> {
+    let guild = msg.guild_id?.to_guild_cached(&ctx.cache)?;
+    let id = msg.channel_id.get();
+
+    if let Some(c) = guild.channels.get(&ChannelId::new(id)) {
+      return Some((None, c.parent_id.map(|p| p.get())));
+    }
+
+    let thread = guild.threads.iter().find(|t| t.id.get() == id)?;
+    let parent = guild.channels.get(&thread.parent_id)?;
+    Some((Some(thread.parent_id.get()), parent.parent_id.map(|p| p.get())))
+  }
+
+  async fn message_scope(
+    &self,
+    ctx: &Context,
+    msg: &Message
+  ) -> Result<MessageScope, BotError> {
+    let member = msg.member(ctx).await?;
+    let mut channels = vec![msg.channel_id.get()];
+
+    let (thread_parent, category) = match Self::cached_location(ctx, msg) {
+      Some(location) => location,
+      None => match ctx.http().get_channel(msg.channel_id).await? {
+        Channel::Guild(c) => (None, c.parent_id.map(|p| p.get())),
+        Channel::GuildThread(t) => {
+          let category = ctx
+            .http()
+            .get_channel(t.parent_id.widen())
+            .await?
+            .guild()
+            .and_then(|c| c.parent_id.map(|p| p.get()));
+          (Some(t.parent_id.get()), category)
+        },
+        _ => (None, None)
+      }
+    };
+    channels.extend(thread_parent);
+
+    Ok(MessageScope {
+      roles: member.roles.iter().map(|r| r.get()).collect(),
+      channels,
+      category
+    })
   }
 
   pub async fn process_message(
@@ -248,245 +957,880 @@ impl Automoderator {
     ctx: &Context,
     msg: &Message
   ) -> Result<(), BotError> {
-    if let Some(violation) = self.check_violations(msg).await {
-      match msg.member(ctx).await {
-        Ok(m) => {
-          if self.staff_check(&m) {
-            println!("[automod::process_message] {} has a staff role, ignoring", msg.author.name);
-            return Ok(());
-          }
-        },
-        Err(_) => {
-          eprintln!("[automod::process_message] Got hit by an error, couldn't check anyway!");
-          return Ok(());
-        }
+    self.process(ctx, msg, Incoming::from(msg), false).await
+  }
+
+  /// Same checks as a new message, minus the rate-based ones since the edit isn't a new message.<br>
+  /// Attachments are only scanned again if they changed from `previous`, or if there's nothing to compare against.
+  pub async fn process_edit(
+    &self,
+    ctx: &Context,
+    msg: &Message,
+    previous: Option<&[AttachmentId]>
+  ) -> Result<(), BotError> {
+    let mut incoming = Incoming::from(msg);
+    let unchanged = previous.is_some_and(|before| before.len() == msg.attachments.len() && msg.attachments.iter().all(|a| before.contains(&a.id)));
+    if unchanged {
+      incoming.attachments.clear();
+    }
+
+    self.process(ctx, msg, incoming, true).await
+  }
+
+  /// Check the member's username, display name and nickname against the prohibited words and malicious links.<br>
+  /// Returns whether the member's nickname got reset.
+  pub async fn screen_member(
+    &self,
+    ctx: &Context,
+    member: &Member
+  ) -> Result<bool, BotError> {
+    if member.user.bot() {
+      return Ok(false);
+    }
+
+    let names = [
+      ("Username", Some(member.user.name.as_str())),
+      ("Display name", member.user.global_name.as_deref()),
+      ("Nickname", member.nick.as_deref())
+    ];
+    let roles: Vec<u64> = member.roles.iter().map(|r| r.get()).collect();
+
+    let Some((policy, name, detection)) = self.triage_profile(member.user.id.get(), roles, &names).await? else {
+      return Ok(false);
+    };
+
+    let subject = Subject::profile(member);
+    if policy.shadow {
+      self.record_event(ctx, &subject, &policy, &detection, "Shadow", None).await;
+      self.log_shadow(ctx, &subject, &policy, &detection).await?;
+      return Ok(false);
+    }
+
+    let key = profile_key(member.user.id.get());
+    // Remembered just before the reset since its own member update can beat the API call back, forgotten again if it fails
+    self.backend.set(&key, name, Some(PROFILE_CACHE_SECS)).await?;
+
+    // Resetting the nickname is what deleting the message is to the other policies
+    if member.nick.as_deref() != Some(nickname::FALLBACK_NAME) {
+      let reset = member
+        .guild_id
+        .edit_member(
+          &ctx.http,
+          member.user.id,
+          EditMember::new()
+            .nickname(nickname::FALLBACK_NAME)
+            .audit_log_reason(&format!("(Automod) {}", policy.reason))
+        )
+        .await;
+
+      if let Err(e) = reset {
+        self.backend.del(&key).await?;
+        return Err(e.into());
       }
+    }
+
+    self.escalate(ctx, &subject, policy, detection).await?;
+    Ok(true)
+  }
+
+  /// Find the name that trips the Profile policy, unless it was already acted on or the member is exempt.<br>
+  /// Names caught in shadow mode are remembered here, otherwise every member update would log them again.
+  async fn triage_profile<'a>(
+    &self,
+    user_id: u64,
+    roles: Vec<u64>,
+    names: &[(&str, Option<&'a str>)]
+  ) -> Result<Option<(AutomodPolicy, &'a str, Detection)>, BotError> {
+    let policy = match self
+      .policies
+      .read()
+      .await
+      .iter()
+      .find(|p| p.enabled && p.policy_type == AutomodPolicyType::Profile)
+    {
+      Some(p) => p.clone(),
+      None => return Ok(None)
+    };
+
+    let (name, detection) = match self.find_profile_violation(names) {
+      Some(d) => d,
+      None => return Ok(None)
+    };
+
+    // Member updates fire for role changes and our own nickname reset too, only act on a name once
+    let key = profile_key(user_id);
+    if self.backend.get(&key).await?.as_deref() == Some(name) {
+      return Ok(None);
+    }
+
+    let scope = MessageScope {
+      roles,
+      channels: Vec::new(),
+      category: None
+    };
+    if self.exempts.read().unwrap().iter().any(|e| e.covers(&policy.policy_type, &scope)) {
+      println!("[automod::screen_member] {user_id} is exempt from profile screening, ignoring");
+      return Ok(None);
+    }
 
-      if violation.policy_type == AutomodPolicyType::MaliciousLinks {
-        println!("[automod::process_message] ({}) Malicious URL: {}", msg.author.name, msg.content);
+    if policy.shadow {
+      self.backend.set(&key, name, Some(PROFILE_CACHE_SECS)).await?;
+    }
+
+    Ok(Some((policy, name, detection)))
+  }
+
+  /// Wipe the user's warnings towards the policy, used when staff pardons a case
+  pub async fn pardon_warnings(
+    &self,
+    user_id: UserId,
+    policy_type: &AutomodPolicyType
+  ) -> Result<(), BotError> {
+    self.backend.del(&warnings_key(user_id.get(), policy_type)).await
+  }
+
+  /// Count a warning towards the policy, the count expires once the user has gone `decay_secs` without a new one.<br>
+  /// Returns whether the user has hit the threshold, the count starts over if they have.
+  async fn count_warning(
+    &self,
+    user_id: u64,
+    policy: &AutomodPolicy
+  ) -> Result<bool, BotError> {
+    // Nothing to count towards when the policy never escalates
+    if policy.delete_only {
+      return Ok(false);
+    }
+
+    let key = warnings_key(user_id, &policy.policy_type);
+    let warnings = self.backend.incr(&key, policy.decay_secs.max(1) as i64).await?;
+
+    let should_action = policy.escalates_at(warnings);
+    if should_action {
+      self.backend.del(&key).await?;
+    }
+
+    Ok(should_action)
+  }
+
+  /// What the user's warning count would be after their next warning, without counting it
+  async fn next_warning(
+    &self,
+    user_id: u64,
+    policy: &AutomodPolicy
+  ) -> Result<i64, BotError> {
+    let warnings = self.backend.get(&warnings_key(user_id, &policy.policy_type)).await?;
+    Ok(warnings.and_then(|w| w.parse::<i64>().ok()).unwrap_or(0) + 1)
+  }
+
+  async fn process(
+    &self,
+    ctx: &Context,
+    msg: &Message,
+    incoming: Incoming<'_>,
+    edited: bool
+  ) -> Result<(), BotError> {
+    let violations = self.check_violations(&incoming, edited).await;
+    if violations.is_empty() {
+      return Ok(());
+    }
+
+    let scope = match self.message_scope(ctx, msg).await {
+      Ok(s) => s,
+      Err(e) => {
+        eprintln!("[automod::process_message] Got hit by an error, couldn't check anyway! {e}");
+        return Ok(());
       }
+    };
 
-      self.handle_violation(ctx, msg, violation).await?;
+    let exemptions = self.exempts.read().unwrap().clone();
+    let (shadowed, enforced): (Vec<_>, Vec<_>) = violations
+      .into_iter()
+      .filter(|(p, _)| !exemptions.iter().any(|e| e.covers(&p.policy_type, &scope)))
+      .partition(|(p, _)| p.shadow);
+
+    for (policy, detection) in shadowed.iter() {
+      self.record_event(ctx, &Subject::message(msg), policy, detection, "Shadow", None).await;
+      if let Err(e) = self.log_shadow(ctx, &Subject::message(msg), policy, detection).await {
+        eprintln!(
+          "[automod::process_message] Failed to log the shadowed {} violation: {e}",
+          policy.policy_type.as_str()
+        );
+      }
     }
 
-    Ok(())
+    // First violated policy the message isn't exempt from wins
+    let (violation, detection) = match enforced.into_iter().next() {
+      Some(v) => v,
+      None => {
+        if shadowed.is_empty() {
+          println!("[automod::process_message] {} is exempt here, ignoring", msg.author.name);
+        }
+        return Ok(());
+      }
+    };
+
+    if violation.policy_type == AutomodPolicyType::MaliciousLinks {
+      println!("[automod::process_message] ({}) Malicious URL: {}", msg.author.name, msg.content);
+    }
+
+    self.handle_violation(ctx, msg, violation, detection).await
   }
 
+  /// Returns every enabled policy the message violates, in order of priority
   async fn check_violations(
     &self,
-    msg: &Message
-  ) -> Option<AutomodPolicy> {
+    msg: &Incoming<'_>,
+    edited: bool
+  ) -> Vec<(AutomodPolicy, Detection)> {
     let policies = self.policies.read().await;
-    let current_ts = msg.timestamp.unix_timestamp();
+    let enabled = |t: AutomodPolicyType| policies.iter().any(|p| p.enabled && p.policy_type == t);
+    // Edits were already counted towards the rate-based policies when they were sent
+    let activity = if edited {
+      Activity::default()
+    } else {
+      self.track_activity(msg, &policies).await
+    };
+    // Downloading attachments isn't free, skip it if nothing would come out of it
+    let scan_attachments = !msg.attachments.is_empty() && enabled(AutomodPolicyType::Attachments);
+    // Resolving invites costs an API call, same deal
+    let check_invites = enabled(AutomodPolicyType::InviteLinks);
+
+    let invites = if check_invites {
+      self.find_invite_link(msg.content, true).await
+    } else {
+      None
+    };
+    let attachment = if scan_attachments {
+      self.find_dangerous_attachment(msg).await
+    } else {
+      None
+    };
 
+    let link_text = msg.link_text();
     let checks = [
-      (AutomodPolicyType::InviteLinks, self.contains_invite_links(&msg.content)),
+      (AutomodPolicyType::InviteLinks, invites),
+      (
+        AutomodPolicyType::DuplicateMessage,
+        activity
+          .duplicate
+          .then(|| Detection::rate("Same message posted across channels".to_string()))
+      ),
+      (
+        AutomodPolicyType::MassMention,
+        activity
+          .mass_mention
+          .then(|| Detection::rate("Too many mentions in a short time".to_string()))
+      ),
       (
         AutomodPolicyType::AntiSpam,
-        self.is_spam(msg.author.id.get(), msg.timestamp.unix_timestamp()).await
+        activity.spam.then(|| Detection::rate("Too many messages in a short time".to_string()))
       ),
-      (AutomodPolicyType::ProhibitedWords, self.contains_prohibited_words(&msg.content)),
-      (AutomodPolicyType::MaliciousLinks, self.contains_malicious_links(&msg.content).await),
-      (AutomodPolicyType::ProhibitedUrls, self.contains_prohibited_urls(&msg.content))
+      (AutomodPolicyType::ProhibitedWords, self.find_prohibited_word(msg.content)),
+      (AutomodPolicyType::MaliciousLinks, self.find_malicious_link(&link_text)),
+      (AutomodPolicyType::Attachments, attachment),
+      (AutomodPolicyType::ProhibitedUrls, self.find_prohibited_url(&link_text)),
+      (AutomodPolicyType::NewAccount, self.find_restricted_newcomer(msg, &policies))
     ];
 
-    for (policy_type, violated) in checks {
-      if violated {
-        if let Some(policy) = policies.iter().find(|p| p.enabled && p.policy_type == policy_type) {
-          let user_stats_key = format!("Discord:UserStats:{}", msg.author.id.get());
-          if let Ok(Some(d)) = self.redis.get(&user_stats_key).await {
-            let stats: UserMessageStats = serde_json::from_str(&d).unwrap_or_default();
-            stats.check_and_reset_warns(&policy_type, current_ts, 300); // 5m
-            let data = serde_json::to_string(&stats).unwrap();
-            self.redis.set(&user_stats_key, &data).await.unwrap();
-          }
-          return Some(policy.clone());
-        }
-      }
-    }
-
-    None
+    checks
+      .into_iter()
+      .filter_map(|(policy_type, detection)| {
+        let policy = policies.iter().find(|p| p.enabled && p.policy_type == policy_type)?;
+        Some((policy.clone(), detection?))
+      })
+      .collect()
   }
 
-  async fn is_spam(
+  /// Run the text through the enabled content policies without acting on it or touching anyone's stats<br>
+  /// Returns each policy that matched and why
+  pub async fn dry_run(
     &self,
-    user_id: u64,
-    timestamp: i64
-  ) -> bool {
-    let user_stats_key = format!("Discord:UserStats:{user_id}");
+    content: &str
+  ) -> Vec<(AutomodPolicy, String)> {
+    let policies = self.policies.read().await;
+    let invites = if policies.iter().any(|p| p.enabled && p.policy_type == AutomodPolicyType::InviteLinks) {
+      self.find_invite_link(content, false).await
+    } else {
+      None
+    };
 
-    let mut stats: UserMessageStats = match self.redis.get(&user_stats_key).await {
-      Ok(Some(d)) => serde_json::from_str(&d).unwrap_or_default(),
-      Ok(None) => UserMessageStats::default(),
-      Err(_) => UserMessageStats::default()
+    let checks = [
+      (AutomodPolicyType::InviteLinks, invites),
+      (AutomodPolicyType::ProhibitedWords, self.find_prohibited_word(content)),
+      (AutomodPolicyType::MaliciousLinks, self.find_malicious_link(content)),
+      (AutomodPolicyType::ProhibitedUrls, self.find_prohibited_url(content))
+    ];
+
+    checks
+      .into_iter()
+      .filter_map(|(policy_type, detection)| {
+        let policy = policies.iter().find(|p| p.enabled && p.policy_type == policy_type)?;
+        Some((policy.clone(), detection?.reason))
+      })
+      .collect()
+  }
+
+  /// Record the message and check it against the rate-based policies
+  async fn track_activity(
+    &self,
+    msg: &Incoming<'_>,
+    policies: &[AutomodPolicy]
+  ) -> Activity {
+    let timestamp = msg.timestamp;
+    let policy = |t: AutomodPolicyType| {
+      policies
+        .iter()
+        .find(|p| p.policy_type == t)
+        .cloned()
+        .unwrap_or(AutomodPolicy::default_for(&t))
     };
 
-    stats.messages.retain(|t| timestamp - *t <= 5);
-    stats.messages.push(timestamp);
+    let user_id = msg.author_id;
+    let mut activity = Activity::default();
+
+    let anti_spam = policy(AutomodPolicyType::AntiSpam);
+    let messages = self
+      .push_activity(
+        &format!("{MESSAGES_KEY}:{user_id}"),
+        timestamp,
+        &msg.message_id.to_string(),
+        anti_spam.window_secs
+      )
+      .await;
+    activity.spam = messages.len() >= anti_spam.trigger_threshold as usize;
+
+    let mass_mention = policy(AutomodPolicyType::MassMention);
+    if msg.mentions > 0 {
+      // Members are `{message_id}:{mentions}` so each message is counted once
+      let mentions: u32 = self
+        .push_activity(
+          &format!("{MENTIONS_KEY}:{user_id}"),
+          timestamp,
+          &format!("{}:{}", msg.message_id, msg.mentions),
+          mass_mention.window_secs
+        )
+        .await
+        .iter()
+        .filter_map(|m| m.rsplit_once(':').and_then(|(_, n)| n.parse::<u32>().ok()))
+        .sum();
+      activity.mass_mention = mentions >= mass_mention.trigger_threshold;
+    }
 
-    let data = serde_json::to_string(&stats).unwrap();
-    self.redis.set(&user_stats_key, &data).await.unwrap();
+    let duplicate = policy(AutomodPolicyType::DuplicateMessage);
+    let content = duplicate_fingerprint(msg.content);
+    if content.chars().count() >= MIN_DUPLICATE_LEN {
+      let current = RecentMessage {
+        timestamp,
+        channel_id: msg.channel_id,
+        message_id: msg.message_id,
+        content
+      };
+      let member = serde_json::to_string(&current).expect("RecentMessage always serializes");
 
-    stats.messages.len() >= 4
+      let mut channels: Vec<u64> = self
+        .push_activity(&format!("{RECENT_KEY}:{user_id}"), timestamp, &member, duplicate.window_secs)
+        .await
+        .iter()
+        .filter_map(|m| serde_json::from_str::<RecentMessage>(m).ok())
+        .filter(|m| is_duplicate(&m.content, &current.content))
+        .map(|m| m.channel_id)
+        .collect();
+      channels.sort_unstable();
+      channels.dedup();
+      activity.duplicate = channels.len() >= duplicate.trigger_threshold as usize;
+    }
+
+    activity
   }
 
-  fn contains_prohibited_words(
+  /// Add the entry to one of the user's activity windows and return what's still in it.<br>
+  /// Failures are logged and count as an empty window, so a Redis hiccup never sanctions anyone.
+  async fn push_activity(
     &self,
-    content: &str
-  ) -> bool {
-    self.pw_list.iter().any(|r| r.is_match(content))
+    key: &str,
+    timestamp: i64,
+    member: &str,
+    window_secs: u32
+  ) -> Vec<String> {
+    let window = window_secs.max(1) as i64;
+    match self.backend.push_window(key, timestamp, member, timestamp - window, window).await {
+      Ok(members) => members,
+      Err(e) => {
+        eprintln!("[automod::track_activity] Failed to update {key}: {e}");
+        Vec::new()
+      }
+    }
   }
 
-  fn contains_prohibited_urls(
+  /// Clean up the copies of a duplicated message that were posted before it got caught
+  async fn delete_duplicates(
     &self,
-    content: &str
-  ) -> bool {
-    let mut domains = Vec::new();
-    for cap in URL_REGEX.captures_iter(content) {
-      if let Some(domain) = cap.get(1) {
-        domains.push(domain.as_str().to_lowercase());
+    ctx: &Context,
+    msg: &Message,
+    policy: &AutomodPolicy
+  ) {
+    let since = msg.timestamp.unix_timestamp() - policy.window_secs.max(1) as i64;
+    let recent = match self.backend.window(&format!("{RECENT_KEY}:{}", msg.author.id), since).await {
+      Ok(r) => r,
+      Err(e) => {
+        eprintln!("[automod::delete_duplicates] Failed to load {}'s recent messages: {e}", msg.author.id);
+        return;
+      }
+    };
+    let content = duplicate_fingerprint(&msg.content);
+
+    for copy in recent
+      .iter()
+      .filter_map(|m| serde_json::from_str::<RecentMessage>(m).ok())
+      .filter(|m| m.message_id != msg.id.get() && is_duplicate(&m.content, &content))
+    {
+      if let Err(e) = GenericChannelId::new(copy.channel_id)
+        .delete_message(&ctx.http, MessageId::new(copy.message_id), Some("Duplicate message across channels"))
+        .await
+      {
+        eprintln!("[automod::delete_duplicates] Failed to delete a copy in {}: {e}", copy.channel_id);
       }
     }
+  }
+
+  async fn find_dangerous_attachment(
+    &self,
+    msg: &Incoming<'_>
+  ) -> Option<Detection> {
+    let scam_images = self.scam_img.read().unwrap().clone();
+
+    for attachment in msg.attachments.iter() {
+      let filename = attachment.filename;
+      if attachments::is_blocked(filename) {
+        return Some(Detection::new(filename, format!("Blocked file `{filename}`")));
+      }
+
+      let is_archive = attachments::is_archive(filename) && attachment.size <= attachments::MAX_ARCHIVE_SIZE;
+      let is_image = attachments::is_image(filename) && attachment.size <= attachments::MAX_IMAGE_SIZE && !scam_images.is_empty();
+      if !is_archive && !is_image {
+        continue;
+      }
+
+      let Some(data) = self.backend.download(attachment.url).await else {
+        continue;
+      };
+
+      if is_archive {
+        if let Some(inner) = attachments::zip_entries(&data).into_iter().find(|n| attachments::is_blocked(n)) {
+          return Some(Detection::new(inner.as_str(), format!("Blocked file `{inner}` inside `{filename}`")));
+        }
+      }
 
-    for domain in domains {
-      for prohibited in &self.pu_list {
-        if domain == *prohibited || domain.ends_with(&format!(".{prohibited}")) {
-          return true;
+      if is_image {
+        // Decoding the image is CPU-bound, keep it off the async workers
+        let Some(hash) = tokio::task::spawn_blocking(move || dhash(&data)).await.ok().flatten() else {
+          continue;
+        };
+        if let Some((_, label)) = scam_images
+          .iter()
+          .find(|(h, _)| attachments::hash_distance(*h, hash) <= attachments::MAX_HASH_DISTANCE)
+        {
+          return Some(Detection::new(label.as_str(), format!("`{filename}` looks like scam image `{label}`")));
         }
       }
     }
 
-    false
+    None
   }
 
-  fn contains_invite_links(
+  // The content checks below return why the content matched, shared by the live checks and dry runs
+
+  /// First of the labelled names that has a prohibited word or a malicious link in it
+  fn find_profile_violation<'a>(
+    &self,
+    names: &[(&str, Option<&'a str>)]
+  ) -> Option<(&'a str, Detection)> {
+    names.iter().find_map(|(field, name)| {
+      let name = (*name)?;
+      let found = self.find_prohibited_word(name).or_else(|| self.find_malicious_link(name))?;
+      Some((
+        name,
+        Detection {
+          indicator: found.indicator,
+          reason:    format!("{field} `{name}`: {}", found.reason)
+        }
+      ))
+    })
+  }
+
+  fn find_prohibited_word(
     &self,
     content: &str
-  ) -> bool {
-    INVITE_REGEX.is_match(content)
+  ) -> Option<Detection> {
+    self
+      .pw_list
+      .read()
+      .unwrap()
+      .find(content)
+      .map(|w| Detection::new(w, format!("Matched prohibited word `{w}`")))
   }
 
-  async fn contains_malicious_links(
+  fn find_prohibited_url(
     &self,
     content: &str
-  ) -> bool {
-    if let Err(e) = self.update_malicious_domains().await {
-      eprintln!("failed to update malicious domains: {e}");
+  ) -> Option<Detection> {
+    let links = urls::extract(content);
+    let pu_list = self.pu_list.read().unwrap().clone();
+
+    for link in &links {
+      for domain in link.candidates() {
+        for prohibited in pu_list.iter() {
+          if domain == *prohibited || domain.ends_with(&format!(".{prohibited}")) {
+            let reason = if domain == link.host {
+              format!("`{domain}` matched banned URL `{prohibited}`")
+            } else {
+              format!("`{}` imitates `{domain}`, which matched banned URL `{prohibited}`", link.host)
+            };
+            return Some(Detection::new(prohibited, reason));
+          }
+        }
+      }
     }
 
-    let domains: Vec<String> = match self.redis.get(MD_KEY_MAIN).await.unwrap_or(None) {
-      Some(json) => serde_json::from_str(&json).unwrap_or_default(),
-      None => Vec::new()
-    };
+    None
+  }
 
-    // Check regular URLs
-    for cap in URL_REGEX.captures_iter(content) {
-      if let Some(domain) = cap.get(1) {
-        if domains.iter().any(|d| &domain.as_str().to_lowercase() == d) {
-          return true;
-        }
+  /// Invites to the home guild and partner guilds are fine, anything else is advertising
+  /// `remember` caches the resolved invites, dry runs leave it off so they don't touch Redis
+  async fn find_invite_link(
+    &self,
+    content: &str,
+    remember: bool
+  ) -> Option<Detection> {
+    for cap in INVITE_REGEX.captures_iter(content) {
+      let (Some(link), Some(code)) = (cap.get(0), cap.get(1)) else {
+        continue;
+      };
+
+      let guild_id = self.resolve_invite(code.as_str(), remember).await;
+      let allowed = guild_id.is_some_and(|g| g == BINARY_PROPERTIES.guild_id || self.partners.read().unwrap().contains(&g));
+      if !allowed {
+        let reason = match guild_id {
+          Some(g) => format!("Invite link `{}` leads to server `{g}`", link.as_str()),
+          None => format!("Invite link `{}` couldn't be resolved", link.as_str())
+        };
+        return Some(Detection::new(code.as_str(), reason));
       }
     }
 
-    // Check masked URLs
-    for cap in MASKED_URL_REGEX.captures_iter(content) {
-      if let Some(url) = cap.get(1) {
-        if let Ok(parsed) = Url::parse(url.as_str()) {
-          if let Some(domain) = parsed.host_str() {
-            if domains.iter().any(|d| &domain.to_lowercase() == d) {
-              return true;
-            }
-          }
-        }
+    None
+  }
+
+  /// Look up which guild the invite leads to, cached in Redis to keep the API calls down
+  async fn resolve_invite(
+    &self,
+    code: &str,
+    remember: bool
+  ) -> Option<u64> {
+    let key = format!("{INVITE_KEY}:{code}");
+    if let Ok(Some(cached)) = self.backend.get(&key).await {
+      // The sentinel doesn't parse, so it comes back as unresolved
+      return cached.parse::<u64>().ok();
+    }
+
+    let guild_id = self.backend.invite_guild(code).await;
+    if remember {
+      let (value, ttl) = match guild_id {
+        Some(g) => (g.to_string(), INVITE_CACHE_SECS),
+        None => (INVITE_UNRESOLVED.to_string(), INVITE_UNRESOLVED_SECS)
+      };
+      let _ = self.backend.set(&key, &value, Some(ttl)).await;
+    }
+    guild_id
+  }
+
+  fn find_malicious_link(
+    &self,
+    content: &str
+  ) -> Option<Detection> {
+    let domains = self.md_list.read().unwrap().clone();
+    if domains.is_empty() {
+      return None;
+    }
+
+    let allowlist = self.md_allow.read().unwrap().clone();
+    let describe = |link: &urls::Link, domain: &str| {
+      if allowlist.contains(domain) {
+        return None;
       }
+
+      let hit = domains.lookup(domain)?;
+      let shown = if domain == link.host {
+        format!("`{domain}`")
+      } else {
+        format!("`{}` imitates `{domain}`, which", link.host)
+      };
+      let reason = format!(
+        "{}{shown} is listed as `{}` by {}",
+        if link.masked { "Masked link " } else { "" },
+        hit.entry,
+        hit.source.map_or("an unknown source".to_string(), |s| format!("<{s}>"))
+      );
+      Some(Detection::new(hit.entry, reason))
+    };
+
+    // Deobfuscated and with punycode hosts also checked as the domain they're imitating
+    urls::extract(content)
+      .iter()
+      .find_map(|link| link.candidates().find_map(|domain| describe(link, domain)))
+  }
+
+  /// Whether the message carries links, files or mentions from an account or member that's too new
+  fn find_restricted_newcomer(
+    &self,
+    msg: &Incoming<'_>,
+    policies: &[AutomodPolicy]
+  ) -> Option<Detection> {
+    let policy = policies.iter().find(|p| p.enabled && p.policy_type == AutomodPolicyType::NewAccount)?;
+
+    let has_reach = !msg.attachments.is_empty()
+      || msg.mention_everyone
+      || msg.mentions > 0
+      || LINK_REGEX.is_match(msg.content)
+      || INVITE_REGEX.is_match(msg.content);
+    if !has_reach {
+      return None;
     }
 
-    false
+    let now = msg.timestamp;
+    let account_age = now - msg.account_created;
+    let new_account = policy.account_age_days > 0 && account_age < policy.account_age_days as i64 * 86400;
+
+    // Edits don't come with the member, only the account age is checked then
+    let member_age = msg.joined_at.map(|j| now - j);
+    let new_member = policy.member_age_secs > 0 && member_age.is_some_and(|a| a < policy.member_age_secs as i64);
+
+    match (new_account, new_member) {
+      (true, _) => Some(Detection::rate(format!("Account is {} old", format_duration(account_age.max(0) as u64)))),
+      (_, true) => Some(Detection::rate(format!(
+        "Joined {} ago",
+        format_duration(member_age.unwrap_or_default().max(0) as u64)
+      ))),
+      _ => None
+    }
   }
 
-  async fn update_malicious_domains(&self) -> Result<(), BotError> {
-    let last_update = match self.redis.get(MD_KEY_LU).await? {
+  /// Called by the [MaliciousDomains] task, fetches the blocklists if the Redis copy is an hour+ old,
+  /// otherwise pulls the Redis copy into memory if it's newer than what we've got<br>
+  /// `force` skips the age check, used after the sources have been changed
+  pub async fn refresh_malicious_domains(
+    &self,
+    db: &PgPool,
+    force: bool
+  ) -> Result<(), BotError> {
+    let last_update = match self.backend.get(MD_KEY_LU).await? {
       Some(ts) => ts.parse::<i64>().unwrap_or(0),
       None => 0
     };
 
     let current_time = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
 
-    // run an update if hour+ old
-    if current_time - last_update <= 3600 {
-      return Ok(());
+    let newer = last_update > self.md_epoch.load(SeqCst);
+    let cached = if newer { self.cached_malicious_domains().await? } else { None };
+
+    // run an update if hour+ old, or if the Redis copy predates grouping by source
+    if force || current_time - last_update > 3600 || (newer && cached.is_none()) {
+      let fetched = Self::fetch_malicious_domains(db, current_time).await?;
+      let configured = !fetched.is_empty();
+
+      // A source that failed this time keeps the domains from its last good fetch
+      let previous = match &cached {
+        Some(lists) => lists.clone(),
+        None => self.cached_malicious_domains().await?.unwrap_or_default()
+      };
+      let lists: Vec<(String, Vec<String>)> = fetched
+        .into_iter()
+        .filter_map(|(url, entries)| {
+          let entries = entries.or_else(|| previous.iter().find(|(u, _)| *u == url).map(|(_, e)| e.clone()))?;
+          Some((url, entries))
+        })
+        .collect();
+
+      // With every source removed the blocklist has to go too, rather than staying enforced from memory
+      if !lists.is_empty() || !configured {
+        let lists_json = serde_json::to_string(&lists)?;
+        self.backend.set(MD_KEY_MAIN, &lists_json, None).await?;
+        self.backend.set(MD_KEY_LU, &current_time.to_string(), None).await?;
+
+        self.swap_malicious_domains(DomainSet::from_sources(&lists), current_time);
+        return Ok(());
+      }
     }
 
-    let initial_cap = match self.redis.get(MD_KEY_MAIN).await? {
-      Some(j) => {
-        let domains: Vec<String> = serde_json::from_str(&j).unwrap_or_default();
-        (domains.len() + (domains.len() / 10)).max(50000)
-      },
-      None => 80000 // fallback to 80k if Redis key doesn't exist!
-    };
+    if let Some(lists) = cached {
+      self.swap_malicious_domains(DomainSet::from_sources(&lists), last_update);
+    }
+
+    Ok(())
+  }
 
-    let mut domains = Vec::with_capacity(initial_cap);
-    let mut success = 0;
+  async fn cached_malicious_domains(&self) -> Result<Option<Vec<(String, Vec<String>)>>, BotError> {
+    Ok(match self.backend.get(MD_KEY_MAIN).await? {
+      Some(json) => serde_json::from_str(&json).ok(),
+      None => None
+    })
+  }
+
+  fn swap_malicious_domains(
+    &self,
+    domains: DomainSet,
+    epoch: i64
+  ) {
+    println!("MaliciousDomains[Info] Cache refreshed | {} domains total", domains.len());
+    *self.md_list.write().unwrap() = Arc::new(domains);
+    self.md_epoch.store(epoch, SeqCst);
+  }
+
+  /// Fetch every source, returns the domains grouped by the source they came from, `None` for the ones that failed
+  async fn fetch_malicious_domains(
+    db: &PgPool,
+    current_time: i64
+  ) -> Result<Vec<(String, Option<Vec<String>>)>, BotError> {
+    let sources = AutomodSources::get_sources(db).await?;
+    let mut lists = Vec::with_capacity(sources.len());
+    let mut fetched = 0;
     let mut total = 0;
 
-    for url in MD_BLOCKLIST.iter() {
-      match REQWEST_CLIENT
-        .get(*url)
+    for source in sources.iter() {
+      let url = source.url.as_str();
+      let mut request = REQWEST_CLIENT
+        .get(url)
         .header("User-Agent", "Daggerbot - MaliciousDomains Scanner")
-        .header("Authorization", format!("Token {}", token_path().await.octokit))
-        .timeout(Duration::from_secs(10))
-        .send()
-        .await
-      {
-        Ok(r) => {
-          if !r.status().is_success() {
-            eprintln!("MaliciousDomains[Debug] {url} returned status {}", r.status());
-            continue;
-          }
+        .timeout(Duration::from_secs(10));
+      if is_github_source(url) {
+        request = request.header("Authorization", format!("Token {}", token_path().await.octokit));
+      }
 
-          match r.text().await {
-            Ok(txt) => {
-              let entries = Self::process_response_text(txt).await;
-              let count = entries.len();
-              domains.extend(entries);
-              total += count;
-              success += 1;
-            },
-            Err(e) => eprintln!("MaliciousDomains[Err] {url} reported an error: {e}")
-          }
+      let result = match request.send().await {
+        Ok(r) if !r.status().is_success() => Err(format!("Returned status {}", r.status())),
+        Ok(r) => match r.text().await {
+          Ok(txt) => match parse_blocklist(&txt) {
+            entries if entries.is_empty() => Err("No domains could be parsed from the response".to_string()),
+            entries => Ok(entries)
+          },
+          Err(e) => Err(format!("Failed to read the response: {e}"))
         },
-        Err(e) => eprintln!("MaliciousDomains[Err] {url} didn't want to respond: {e}")
+        Err(e) => Err(format!("Didn't respond: {e}"))
+      };
+
+      match result {
+        Ok(entries) => {
+          AutomodSources::record_success(db, url, entries.len() as i32, current_time).await?;
+          fetched += 1;
+          total += entries.len();
+          lists.push((url.to_string(), Some(entries)));
+        },
+        Err(e) => {
+          eprintln!("MaliciousDomains[Err] {url}: {e}");
+          AutomodSources::record_failure(db, url, &e).await?;
+          lists.push((url.to_string(), None));
+        }
       }
     }
 
     println!(
-      "MaliciousDomains[Info] Refreshed from {success} of {} sources, {total} domains total",
-      MD_BLOCKLIST.len()
+      "MaliciousDomains[Info] Refreshed from {fetched} of {} sources, {total} domains total",
+      sources.len()
     );
 
-    if !domains.is_empty() {
-      let domains_json = serde_json::to_string(&domains)?;
-      self.redis.set(MD_KEY_MAIN, &domains_json).await?;
-      self.redis.set(MD_KEY_LU, &current_time.to_string()).await?;
+    Ok(lists)
+  }
 
-      println!("MaliciousDomains[Info] Cache refreshed | {} domains total", domains.len());
-    }
+  /// Persist the detection for the stats, failing to do so shouldn't get in the way of moderating
+  async fn record_event(
+    &self,
+    ctx: &Context,
+    subject: &Subject<'_>,
+    policy: &AutomodPolicy,
+    detection: &Detection,
+    action: &str,
+    case_id: Option<i32>
+  ) {
+    let event = AutomodEvents {
+      policy_type: policy.policy_type.as_str().to_string(),
+      user_id: subject.user.id.get() as i64,
+      channel_id: subject.message.map(|m| m.channel_id.get() as i64),
+      action: action.to_string(),
+      case_id,
+      indicator: detection.indicator.as_ref().map(|i| i.chars().take(255).collect()),
+      timestamp: subject.timestamp.unix_timestamp()
+    };
 
-    Ok(())
+    if let Err(e) = event.record(&ctx.data::<BotData>().postgres).await {
+      eprintln!(
+        "[automod::record_event] Failed to record the {} detection: {e}",
+        policy.policy_type.as_str()
+      );
+    }
   }
 
-  async fn process_response_text(text: String) -> Vec<String> {
-    text
-      .lines()
-      .map(|line| line.trim().to_lowercase())
-      .filter(|line| !line.is_empty())
-      .collect()
+  /// Report what a policy in shadow mode would've done, without touching the message or the user
+  async fn log_shadow(
+    &self,
+    ctx: &Context,
+    subject: &Subject<'_>,
+    policy: &AutomodPolicy,
+    detection: &Detection
+  ) -> Result<(), BotError> {
+    // Going by the user's current count, so the log shows whether this one would've tipped them over
+    let warnings = self.next_warning(subject.user.id.get(), policy).await?;
+    let outcome = if policy.escalates_at(warnings) {
+      match (&policy.action, policy.mute_duration) {
+        (ActionType::Mute, Some(duration)) => format!("{}, muted for {}", policy.action, format_duration(duration as u64)),
+        _ => policy.action.to_string()
+      }
+    } else if policy.delete_only {
+      "Delete".to_string()
+    } else {
+      format!("Delete, warning {warnings} of {}", policy.warn_threshold)
+    };
+
+    let user = subject.user;
+    let mut fields = vec![("User", format!("{}\n{}\n`{}`", user.name, user.mention(), user.id), true)];
+
+    if let Some(msg) = subject.message {
+      fields.push(("Message", msg.link(), true));
+    }
+
+    fields.push(("Detection", detection.reason.clone(), false));
+    fields.push(("Would have", outcome, false));
+
+    if let Some(msg) = subject.message {
+      let content: String = msg.content.chars().take(1000).collect();
+      fields.push((
+        "Content",
+        if content.is_empty() {
+          "*No text*".to_string()
+        } else {
+          format!("```\n{content}\n```")
+        },
+        false
+      ));
+    }
+
+    let embed = CreateEmbed::new()
+      .color(BINARY_PROPERTIES.embed_colors.yellow)
+      .title(format!("Shadow | {}", policy.policy_type.as_str()))
+      .fields(fields)
+      .timestamp(subject.timestamp);
+
+    LogChannel::BotLog
+      .to_discord()
+      .send_message(&ctx.http, CreateMessage::new().embed(embed))
+      .await?;
+
+    Ok(())
   }
 
   async fn log_violation(
     &self,
     ctx: &Context,
-    msg: &Message,
+    subject: &Subject<'_>,
     policy: &AutomodPolicy,
     case_id: i32
   ) -> Result<(), BotError> {
@@ -497,13 +1841,10 @@ impl Automoderator {
 
     let bot_user = ctx.cache.current_user().id;
     let reason = format!("(Automod) {}", policy.reason);
+    let user = subject.user;
 
     let mut fields = vec![
-      (
-        "User",
-        format!("{}\n{}\n`{}`", msg.author.name, msg.author.mention(), msg.author.id),
-        true
-      ),
+      ("User", format!("{}\n{}\n`{}`", user.name, user.mention(), user.id), true),
       (
         "Moderator",
         format!("{}\n{}\n`{bot_user}`", bot_user.to_user(ctx).await?.name, bot_user.mention()),
@@ -520,7 +1861,7 @@ impl Automoderator {
     let embed = CreateEmbed::default()
       .color(BINARY_PROPERTIES.embed_colors.primary)
       .title(format!("{} | Case #{case_id}", policy.action))
-      .timestamp(msg.timestamp)
+      .timestamp(subject.timestamp)
       .fields(fields);
 
     let channel = ctx
@@ -530,12 +1871,23 @@ impl Automoderator {
       .guild()
       .expect("Log channel not found");
 
-    channel.send_message(&ctx.http, CreateMessage::new().embed(embed)).await?;
+    let pardon = CreateButton::new(format!("{PARDON_BUTTON}:{case_id}:{}", policy.policy_type.as_str()))
+      .label("Pardon")
+      .style(ButtonStyle::Success);
+
+    channel
+      .send_message(
+        &ctx.http,
+        CreateMessage::new()
+          .embed(embed)
+          .components(vec![CreateActionRow::Buttons(Cow::Owned(vec![pardon]))])
+      )
+      .await?;
 
     send_notification(
       ctx,
-      msg,
-      &Target::User(msg.author.clone()),
+      subject.guild_id,
+      &Target::User(user.clone()),
       &policy.action,
       &reason,
       case_id,
@@ -550,100 +1902,111 @@ impl Automoderator {
     &self,
     ctx: &Context,
     msg: &Message,
-    policy: AutomodPolicy
+    policy: AutomodPolicy,
+    detection: Detection
   ) -> Result<(), BotError> {
-    let user_id = msg.author.id.get();
-    let user_stats_key = format!("Discord:UserStats:{user_id}");
-    let user_stats: UserMessageStats = match self.redis.get(&user_stats_key).await? {
-      Some(d) => serde_json::from_str(&d)?,
-      None => UserMessageStats::default()
+    let reply_to_msg = match policy.policy_type {
+      AutomodPolicyType::AntiSpam => "Stop spamming!",
+      AutomodPolicyType::InviteLinks => "Discord invite links aren't allowed in this server!",
+      AutomodPolicyType::ProhibitedWords => "Watch your language!",
+      AutomodPolicyType::MaliciousLinks => "Phishing links aren't allowed in this server!",
+      AutomodPolicyType::ProhibitedUrls => "That link is currently banned in this server!",
+      AutomodPolicyType::MassMention => "Don't mass mention people!",
+      AutomodPolicyType::DuplicateMessage => "Stop posting the same message everywhere!",
+      AutomodPolicyType::Attachments => "That file isn't allowed in this server!",
+      AutomodPolicyType::NewAccount => "Welcome! New accounts can't post links, files or mentions here just yet, try again in a little while.",
+      AutomodPolicyType::Profile => {
+        eprintln!("[automod::handle_violation] Profile policy came through a message, profiles are screened through screen_member");
+        return Ok(());
+      }
     };
 
+    if policy.policy_type == AutomodPolicyType::DuplicateMessage {
+      self.delete_duplicates(ctx, msg, &policy).await;
+    }
+
+    if let Ok(reply) = msg.reply(&ctx.http, reply_to_msg).await {
+      let http = ctx.http.clone();
+      let reply_id = reply.id;
+      let channel_id = reply.channel_id;
+      tokio::spawn(async move {
+        sleep(Duration::from_secs(10)).await;
+        if let Err(e) = channel_id.delete_message(&http, reply_id, None).await {
+          eprintln!("[automod::delete_reply_message] Failed to delete the bot's reply message: {e}");
+        }
+      });
+    } else {
+      eprintln!("[automod::reply_message] Failed to reply to user's message");
+    }
+
+    if let Err(e) = msg.delete(&ctx.http, Some("Message violated the automod's policy!")).await {
+      eprintln!("[automod::delete_message] Failed to delete the message: {e}");
+    }
+
+    self.escalate(ctx, &Subject::message(msg), policy, detection).await
+  }
+
+  /// Count the warning towards the policy and sanction the user once they've hit the threshold
+  async fn escalate(
+    &self,
+    ctx: &Context,
+    subject: &Subject<'_>,
+    policy: AutomodPolicy,
+    detection: Detection
+  ) -> Result<(), BotError> {
+    let user_id = subject.user.id;
     let postgres = &ctx.data::<BotData>().postgres.clone();
     let case_id = generate_id(postgres).await?;
 
     if (Sanctions::load_data(postgres, case_id).await?).is_some() {
       eprintln!(
         "[automod::handle_violation] attempted to create case entry but database already has it: #{case_id} - {}",
-        msg.author.name
+        subject.user.name
       );
       return Ok(())
     }
 
-    let current_ts = msg.timestamp.unix_timestamp();
-    let new_warnings = user_stats.increment_warnings(&policy.policy_type, current_ts);
-
-    let user_stats_data = serde_json::to_string(&user_stats)?;
-    self.redis.set(&user_stats_key, &user_stats_data).await?;
-
-    {
-      let reply_to_msg = match policy.policy_type {
-        AutomodPolicyType::AntiSpam => "Stop spamming!",
-        AutomodPolicyType::InviteLinks => "Discord invite links aren't allowed in this server!",
-        AutomodPolicyType::ProhibitedWords => "Watch your language!",
-        AutomodPolicyType::MaliciousLinks => "Phishing links aren't allowed in this server!",
-        AutomodPolicyType::ProhibitedUrls => "That link is currently banned in this server!"
-      };
-
-      if let Ok(reply) = msg.reply(&ctx.http, reply_to_msg).await {
-        let http = ctx.http.clone();
-        let reply_id = reply.id;
-        let channel_id = reply.channel_id;
-        tokio::spawn(async move {
-          sleep(Duration::from_secs(10)).await;
-          if let Err(e) = channel_id.delete_message(&http, reply_id, None).await {
-            eprintln!("[automod::delete_reply_message] Failed to delete the bot's reply message: {e}");
-          }
-        });
-      } else {
-        eprintln!("[automod::reply_message] Failed to reply to user's message");
-      }
-
-      if let Err(e) = msg.delete(&ctx.http, Some("Message violated the automod's policy!")).await {
-        eprintln!("[automod::delete_message] Failed to delete the message: {e}");
-      }
-    }
+    let should_action = self.count_warning(user_id.get(), &policy).await?;
 
-    let should_action = new_warnings >= policy.warn_threshold;
+    let action = if should_action {
+      policy.action.to_string()
+    } else {
+      "Delete".to_string()
+    };
+    self
+      .record_event(ctx, subject, &policy, &detection, &action, should_action.then_some(case_id))
+      .await;
     if should_action {
-      user_stats.reset_warnings(&policy.policy_type);
-
-      // Save state to external cache
-      let user_stats_data = serde_json::to_string(&user_stats)?;
-      self.redis.set(&user_stats_key, &user_stats_data).await?;
-
-      self.log_violation(ctx, msg, &policy, case_id).await?;
+      self.log_violation(ctx, subject, &policy, case_id).await?;
 
+      let guild_id = subject.guild_id;
       match policy.action {
-        ActionType::Warn => self.create_sanction(ctx, msg.author.id, "Warn", &policy.reason, None, case_id).await?,
+        ActionType::Warn => self.create_sanction(ctx, user_id, "Warn", &policy.reason, None, case_id).await?,
         ActionType::Mute => {
           if let Some(duration) = policy.mute_duration {
-            let guild_id = msg.guild_id.expect("Expected message to be in guild");
-            if let Ok(mut member) = guild_id.member(&ctx.http, msg.author.id).await {
-              let until = Timestamp::from_unix_timestamp(msg.timestamp.unix_timestamp() + duration).expect("Invalid timestamp");
+            if let Ok(mut member) = guild_id.member(&ctx.http, user_id).await {
+              let until = Timestamp::from_unix_timestamp(subject.timestamp.unix_timestamp() + duration).expect("Invalid timestamp");
               member.disable_communication_until(&ctx.http, until).await?;
               self
-                .create_sanction(ctx, msg.author.id, "Mute", &policy.reason, Some(duration), case_id)
+                .create_sanction(ctx, user_id, "Mute", &policy.reason, Some(duration), case_id)
                 .await?;
             }
           }
         },
         ActionType::Kick => {
-          if let Ok(member) = msg.member(&ctx.http).await {
+          if let Ok(member) = guild_id.member(&ctx.http, user_id).await {
             member.kick(&ctx.http, Some(&policy.reason)).await?;
-            self.create_sanction(ctx, msg.author.id, "Kick", &policy.reason, None, case_id).await?;
+            self.create_sanction(ctx, user_id, "Kick", &policy.reason, None, case_id).await?;
           }
         },
         ActionType::Ban => {
-          let guild_id = msg.guild_id.expect("Expected message to be in guild");
-          guild_id.ban(&ctx.http, msg.author.id, 86400, Some(&policy.reason)).await?;
-          self.create_sanction(ctx, msg.author.id, "Ban", &policy.reason, None, case_id).await?;
+          guild_id.ban(&ctx.http, user_id, 86400, Some(&policy.reason)).await?;
+          self.create_sanction(ctx, user_id, "Ban", &policy.reason, None, case_id).await?;
         },
         ActionType::Softban => {
-          let guild_id = msg.guild_id.expect("Expected message to be in guild");
-          guild_id.ban(&ctx.http, msg.author.id, 86400, Some(&policy.reason)).await?;
-          guild_id.unban(&ctx.http, msg.author.id, None).await?;
-          self.create_sanction(ctx, msg.author.id, "Softban", &policy.reason, None, case_id).await?;
+          guild_id.ban(&ctx.http, user_id, 86400, Some(&policy.reason)).await?;
+          guild_id.unban(&ctx.http, user_id, None).await?;
+          self.create_sanction(ctx, user_id, "Softban", &policy.reason, None, case_id).await?;
         },
         _ => println!("[automod::should_action] Unknown ActionType ended up here!")
       }
@@ -682,26 +2045,27 @@ impl Automoderator {
       timestamp,
       end_time: duration.map(|d| timestamp + d),
       duration,
-      reason: Cow::Borrowed(reason).into_owned()
+      reason: Cow::Borrowed(reason).into_owned(),
+      voided: false
     };
 
     sanction.create(&ctx.data::<BotData>().postgres).await?;
     Ok(())
   }
 
-  async fn load_prohibited_words(db: &PgPool) -> Result<Vec<Regex>, BotError> {
-    let words = ProhibitedWords::get_words(db).await?;
+  async fn load_partners(db: &PgPool) -> Result<HashSet<u64>, BotError> {
+    let partners = AutomodPartners::get_partners(db).await?;
+    Ok(partners.into_iter().map(|p| p.guild_id as u64).collect())
+  }
 
-    let regexes = words
-      .into_iter()
-      .filter_map(|w| {
-        Regex::new(&format!(r"(?i)\b{}(?:ing|ed|s|[0-9]*)?\b", regex::escape(&w.word)))
-          .map_err(|e| eprintln!("Invalid word pattern ({}): {e}", w.word))
-          .ok()
-      })
-      .collect();
+  async fn load_scam_images(db: &PgPool) -> Result<Vec<(u64, String)>, BotError> {
+    let images = ScamImages::get_images(db).await?;
+    Ok(images.into_iter().map(|i| (i.hash as u64, i.label)).collect())
+  }
 
-    Ok(regexes)
+  async fn load_allowlist(db: &PgPool) -> Result<DomainSet, BotError> {
+    let domains = AutomodAllowlist::get_domains(db).await?;
+    Ok(DomainSet::new(domains.iter().map(|d| d.domain.as_str())))
   }
 
   async fn load_prohibited_urls(db: &PgPool) -> Result<Vec<String>, BotError> {
@@ -714,7 +2078,7 @@ impl Automoderator {
 /// Send a notification to a user about a moderation action
 async fn send_notification(
   ctx: &Context,
-  msg: &Message,
+  guild_id: GuildId,
   target: &Target,
   action: &ActionType,
   reason: &str,
@@ -736,7 +2100,7 @@ async fn send_notification(
       ActionType::Warn => "warned",
       _ => ""
     },
-    msg.guild_id.unwrap().to_partial_guild(&ctx.http).await?.name
+    guild_id.to_partial_guild(&ctx.http).await?.name
   );
 
   let mut fields = vec![("Case ID", case_id.to_string(), true)];
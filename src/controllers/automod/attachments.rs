@@ -0,0 +1,109 @@
+use image::imageops::FilterType;
+
+/// Executables and scripts that have no business being posted in the server
+const BLOCKED_EXTENSIONS: [&str; 22] = [
+  "exe", "scr", "bat", "cmd", "com", "pif", "msi", "msp", "vbs", "vbe", "js", "jse", "wsf", "wsh", "ps1", "lnk", "hta", "jar", "cpl", "reg", "dll",
+  "iso"
+];
+/// Archives that get opened up to look for blocked files inside
+const ARCHIVE_EXTENSIONS: [&str; 1] = ["zip"];
+const IMAGE_EXTENSIONS: [&str; 5] = ["png", "jpg", "jpeg", "webp", "gif"];
+
+/// Archives bigger than this aren't downloaded
+pub const MAX_ARCHIVE_SIZE: u32 = 8 * 1024 * 1024;
+/// Images bigger than this aren't hashed
+pub const MAX_IMAGE_SIZE: u32 = 10 * 1024 * 1024;
+/// Number of differing bits for two images to still be considered the same
+pub const MAX_HASH_DISTANCE: u32 = 8;
+
+const EOCD_SIGNATURE: u32 = 0x06054B50;
+const CENTRAL_HEADER_SIGNATURE: u32 = 0x02014B50;
+/// End of central directory record plus the longest possible comment
+const EOCD_SEARCH_LEN: usize = 22 + u16::MAX as usize;
+
+fn extension(filename: &str) -> Option<String> { filename.rsplit_once('.').map(|(_, ext)| ext.trim().to_lowercase()) }
+
+pub fn is_blocked(filename: &str) -> bool { extension(filename).is_some_and(|e| BLOCKED_EXTENSIONS.contains(&e.as_str())) }
+
+pub fn is_archive(filename: &str) -> bool { extension(filename).is_some_and(|e| ARCHIVE_EXTENSIONS.contains(&e.as_str())) }
+
+pub fn is_image(filename: &str) -> bool { extension(filename).is_some_and(|e| IMAGE_EXTENSIONS.contains(&e.as_str())) }
+
+fn read_u16(
+  data: &[u8],
+  at: usize
+) -> Option<u16> {
+  data.get(at..at + 2).map(|b| u16::from_le_bytes([b[0], b[1]]))
+}
+
+fn read_u32(
+  data: &[u8],
+  at: usize
+) -> Option<u32> {
+  data.get(at..at + 4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+/// List the file names in a zip archive by walking its central directory.<br>
+/// Nothing gets decompressed, so zip bombs are harmless here.
+pub fn zip_entries(data: &[u8]) -> Vec<String> {
+  let search_from = data.len().saturating_sub(EOCD_SEARCH_LEN);
+  let eocd = match (search_from..data.len().saturating_sub(3))
+    .rev()
+    .find(|&i| read_u32(data, i) == Some(EOCD_SIGNATURE))
+  {
+    Some(i) => i,
+    None => return Vec::new()
+  };
+
+  let (Some(count), Some(offset)) = (read_u16(data, eocd + 10), read_u32(data, eocd + 16)) else {
+    return Vec::new();
+  };
+
+  let mut names = Vec::with_capacity(count as usize);
+  let mut pos = offset as usize;
+
+  for _ in 0..count {
+    if read_u32(data, pos) != Some(CENTRAL_HEADER_SIGNATURE) {
+      break;
+    }
+
+    let (Some(name_len), Some(extra_len), Some(comment_len)) = (read_u16(data, pos + 28), read_u16(data, pos + 30), read_u16(data, pos + 32)) else {
+      break;
+    };
+
+    let name_start = pos + 46;
+    match data.get(name_start..name_start + name_len as usize) {
+      Some(name) => names.push(String::from_utf8_lossy(name).into_owned()),
+      None => break
+    }
+
+    pos = name_start + name_len as usize + extra_len as usize + comment_len as usize;
+  }
+
+  names
+}
+
+/// Difference hash, survives rescaling, recompression and small edits
+pub fn dhash(data: &[u8]) -> Option<u64> {
+  let img = image::load_from_memory(data).ok()?;
+  let small = img.resize_exact(9, 8, FilterType::Triangle).to_luma8();
+
+  let mut hash = 0u64;
+  for y in 0..8 {
+    for x in 0..8 {
+      hash <<= 1;
+      if small.get_pixel(x, y)[0] > small.get_pixel(x + 1, y)[0] {
+        hash |= 1;
+      }
+    }
+  }
+
+  Some(hash)
+}
+
+pub fn hash_distance(
+  a: u64,
+  b: u64
+) -> u32 {
+  (a ^ b).count_ones()
+}
@@ -0,0 +1,350 @@
+use {
+  super::REQWEST_CLIENT,
+  crate::{
+    BotError,
+    controllers::cache::RedisController
+  },
+  poise::serenity_prelude::{
+    Http,
+    async_trait
+  },
+  std::{
+    sync::Arc,
+    time::Duration
+  }
+};
+
+#[cfg(test)]
+use std::{
+  collections::HashMap,
+  sync::{
+    Mutex,
+    atomic::{
+      AtomicI64,
+      Ordering::SeqCst
+    }
+  }
+};
+
+/// Everything the checks need from the outside world.<br>
+/// Detection and escalation only go through this, so they run the same against Redis and Discord or entirely in memory.
+#[async_trait]
+pub trait AutomodBackend: Send + Sync {
+  /// Fetch a value from the shared state
+  async fn get(
+    &self,
+    key: &str
+  ) -> Result<Option<String>, BotError>;
+
+  /// Store a value in the shared state, dropped after `ttl` seconds if there's one
+  async fn set(
+    &self,
+    key: &str,
+    value: &str,
+    ttl: Option<i64>
+  ) -> Result<(), BotError>;
+
+  /// Drop a value from the shared state
+  async fn del(
+    &self,
+    key: &str
+  ) -> Result<(), BotError>;
+
+  /// Atomically bump the counter and push its expiry back to `ttl` seconds, returns the new count
+  async fn incr(
+    &self,
+    key: &str,
+    ttl: i64
+  ) -> Result<i64, BotError>;
+
+  /// Atomically add the member to the window and forget everything scored below `since`.<br>
+  /// The window expires after `ttl` seconds, returns what's left in it, oldest first
+  async fn push_window(
+    &self,
+    key: &str,
+    score: i64,
+    member: &str,
+    since: i64,
+    ttl: i64
+  ) -> Result<Vec<String>, BotError>;
+
+  /// Members of the window scored `since` or later, oldest first
+  async fn window(
+    &self,
+    key: &str,
+    since: i64
+  ) -> Result<Vec<String>, BotError>;
+
+  /// Which guild the invite leads to, `None` if it's invalid or leads to a group DM
+  async fn invite_guild(
+    &self,
+    code: &str
+  ) -> Option<u64>;
+
+  /// Download an attachment to look inside it
+  async fn download(
+    &self,
+    url: &str
+  ) -> Option<Vec<u8>>;
+}
+
+/// Redis for the state, Discord and the CDN for the rest
+pub struct LiveBackend {
+  redis: Arc<RedisController>,
+  http:  Arc<Http>
+}
+
+impl LiveBackend {
+  pub fn new(
+    redis: Arc<RedisController>,
+    http: Arc<Http>
+  ) -> Self {
+    Self { redis, http }
+  }
+}
+
+#[async_trait]
+impl AutomodBackend for LiveBackend {
+  async fn get(
+    &self,
+    key: &str
+  ) -> Result<Option<String>, BotError> {
+    Ok(self.redis.get(key).await?)
+  }
+
+  async fn set(
+    &self,
+    key: &str,
+    value: &str,
+    ttl: Option<i64>
+  ) -> Result<(), BotError> {
+    self.redis.set(key, value).await?;
+    if let Some(ttl) = ttl {
+      self.redis.expire(key, ttl).await?;
+    }
+    Ok(())
+  }
+
+  async fn del(
+    &self,
+    key: &str
+  ) -> Result<(), BotError> {
+    Ok(self.redis.del(key).await?)
+  }
+
+  async fn incr(
+    &self,
+    key: &str,
+    ttl: i64
+  ) -> Result<i64, BotError> {
+    Ok(self.redis.incr_expire(key, ttl).await?)
+  }
+
+  async fn push_window(
+    &self,
+    key: &str,
+    score: i64,
+    member: &str,
+    since: i64,
+    ttl: i64
+  ) -> Result<Vec<String>, BotError> {
+    Ok(self.redis.zadd_window(key, score, member, since, ttl).await?)
+  }
+
+  async fn window(
+    &self,
+    key: &str,
+    since: i64
+  ) -> Result<Vec<String>, BotError> {
+    Ok(self.redis.zrange_since(key, since).await?)
+  }
+
+  async fn invite_guild(
+    &self,
+    code: &str
+  ) -> Option<u64> {
+    match self.http.get_invite(code, false, false, None).await {
+      Ok(invite) => invite.guild.map(|g| g.id.get()),
+      Err(e) => {
+        eprintln!("[automod::resolve_invite] Couldn't resolve invite `{code}`: {e}");
+        None
+      }
+    }
+  }
+
+  async fn download(
+    &self,
+    url: &str
+  ) -> Option<Vec<u8>> {
+    let result = match REQWEST_CLIENT.get(url).timeout(Duration::from_secs(10)).send().await {
+      Ok(r) if r.status().is_success() => r.bytes().await.map(|b| b.to_vec()).map_err(|e| e.to_string()),
+      Ok(r) => Err(format!("Returned status {}", r.status())),
+      Err(e) => Err(e.to_string())
+    };
+
+    result.map_err(|e| eprintln!("[automod::attachments] Failed to download {url}: {e}")).ok()
+  }
+}
+
+/// Keeps everything in memory and never reaches out to Discord, used by the tests.<br>
+/// Expiry runs on its own clock, moved along with [MemoryBackend::advance].
+#[cfg(test)]
+#[derive(Default)]
+pub struct MemoryBackend {
+  clock:   AtomicI64,
+  /// Value and when it expires
+  values:  Mutex<HashMap<String, (String, Option<i64>)>>,
+  /// Scored members and when the window expires
+  windows: Mutex<HashMap<String, (Vec<(i64, String)>, i64)>>,
+  invites: HashMap<String, u64>,
+  files:   HashMap<String, Vec<u8>>
+}
+
+#[cfg(test)]
+impl MemoryBackend {
+  /// Make the invite code lead to the guild
+  pub fn invite(
+    mut self,
+    code: &str,
+    guild_id: u64
+  ) -> Self {
+    self.invites.insert(code.to_string(), guild_id);
+    self
+  }
+
+  /// Serve the data when the URL gets downloaded
+  pub fn file(
+    mut self,
+    url: &str,
+    data: Vec<u8>
+  ) -> Self {
+    self.files.insert(url.to_string(), data);
+    self
+  }
+
+  /// Let time pass, anything that runs out of it expires
+  pub fn advance(
+    &self,
+    secs: i64
+  ) {
+    self.clock.fetch_add(secs, SeqCst);
+  }
+
+  /// Whether nothing is stored, expired entries don't count
+  pub fn is_empty(&self) -> bool {
+    let now = self.clock.load(SeqCst);
+    let values = self.values.lock().unwrap();
+    let windows = self.windows.lock().unwrap();
+    values.values().all(|(_, expiry)| expiry.is_some_and(|e| e <= now)) && windows.values().all(|(_, expiry)| *expiry <= now)
+  }
+
+  fn expiry(
+    &self,
+    ttl: i64
+  ) -> i64 {
+    self.clock.load(SeqCst) + ttl
+  }
+
+  fn live_value(
+    &self,
+    key: &str
+  ) -> Option<String> {
+    let now = self.clock.load(SeqCst);
+    let mut values = self.values.lock().unwrap();
+    if values.get(key).is_some_and(|(_, expiry)| expiry.is_some_and(|e| e <= now)) {
+      values.remove(key);
+    }
+    values.get(key).map(|(v, _)| v.clone())
+  }
+}
+
+#[cfg(test)]
+#[async_trait]
+impl AutomodBackend for MemoryBackend {
+  async fn get(
+    &self,
+    key: &str
+  ) -> Result<Option<String>, BotError> {
+    Ok(self.live_value(key))
+  }
+
+  async fn set(
+    &self,
+    key: &str,
+    value: &str,
+    ttl: Option<i64>
+  ) -> Result<(), BotError> {
+    let expiry = ttl.map(|t| self.expiry(t));
+    self.values.lock().unwrap().insert(key.to_string(), (value.to_string(), expiry));
+    Ok(())
+  }
+
+  async fn del(
+    &self,
+    key: &str
+  ) -> Result<(), BotError> {
+    self.values.lock().unwrap().remove(key);
+    self.windows.lock().unwrap().remove(key);
+    Ok(())
+  }
+
+  async fn incr(
+    &self,
+    key: &str,
+    ttl: i64
+  ) -> Result<i64, BotError> {
+    let count = self.live_value(key).map_or(Ok(0), |v| v.parse::<i64>())? + 1;
+    let expiry = self.expiry(ttl);
+    self.values.lock().unwrap().insert(key.to_string(), (count.to_string(), Some(expiry)));
+    Ok(count)
+  }
+
+  async fn push_window(
+    &self,
+    key: &str,
+    score: i64,
+    member: &str,
+    since: i64,
+    ttl: i64
+  ) -> Result<Vec<String>, BotError> {
+    let now = self.clock.load(SeqCst);
+    let mut windows = self.windows.lock().unwrap();
+    let (members, expiry) = windows.entry(key.to_string()).or_insert_with(|| (Vec::new(), now));
+    if *expiry <= now {
+      members.clear();
+    }
+
+    members.retain(|(s, m)| *s >= since && m != member);
+    members.push((score, member.to_string()));
+    members.sort_by_key(|(s, _)| *s);
+    *expiry = now + ttl;
+
+    Ok(members.iter().map(|(_, m)| m.clone()).collect())
+  }
+
+  async fn window(
+    &self,
+    key: &str,
+    since: i64
+  ) -> Result<Vec<String>, BotError> {
+    let now = self.clock.load(SeqCst);
+    Ok(match self.windows.lock().unwrap().get(key) {
+      Some((members, expiry)) if *expiry > now => members.iter().filter(|(s, _)| *s >= since).map(|(_, m)| m.clone()).collect(),
+      _ => Vec::new()
+    })
+  }
+
+  async fn invite_guild(
+    &self,
+    code: &str
+  ) -> Option<u64> {
+    self.invites.get(code).copied()
+  }
+
+  async fn download(
+    &self,
+    url: &str
+  ) -> Option<Vec<u8>> {
+    self.files.get(url).cloned()
+  }
+}
@@ -0,0 +1,269 @@
+use {
+  crate::{
+    BotData,
+    BotError,
+    internals::scheduler::TaskScheduler
+  },
+  poise::serenity_prelude::async_trait,
+  reqwest::Url,
+  std::{
+    collections::HashMap,
+    net::IpAddr,
+    sync::Arc
+  }
+};
+
+/// In-memory blocklist of malicious domains.<br>
+/// Lookups hash the host and each of its parent domains, so
+/// `sub.evil.com` is caught by an `evil.com` entry without scanning the list.
+#[derive(Default)]
+pub struct DomainSet {
+  /// Domain and the index of the source it came from
+  domains: HashMap<Box<str>, Option<u16>>,
+  sources: Vec<Box<str>>
+}
+
+/// Blocklist entry that a host ran into
+pub struct DomainMatch<'a> {
+  pub entry:  &'a str,
+  pub source: Option<&'a str>
+}
+
+impl DomainSet {
+  pub fn new<I, S>(entries: I) -> Self
+  where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>
+  {
+    let mut set = Self::default();
+    set.insert_all(None, entries);
+    set
+  }
+
+  /// Build the set from several lists, remembering which list each domain came from
+  pub fn from_sources<S: AsRef<str>>(lists: &[(S, Vec<String>)]) -> Self {
+    let mut set = Self::default();
+    for (source, entries) in lists {
+      let idx = set.sources.len() as u16;
+      set.sources.push(source.as_ref().into());
+      set.insert_all(Some(idx), entries);
+    }
+    set
+  }
+
+  fn insert_all<I, S>(
+    &mut self,
+    source: Option<u16>,
+    entries: I
+  ) where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>
+  {
+    for e in entries {
+      let e = e.as_ref().trim().trim_end_matches('.').to_lowercase();
+      if !e.is_empty() {
+        // First list to have the domain keeps it
+        self.domains.entry(e.into_boxed_str()).or_insert(source);
+      }
+    }
+  }
+
+  pub fn len(&self) -> usize { self.domains.len() }
+
+  pub fn is_empty(&self) -> bool { self.domains.is_empty() }
+
+  /// Check the host and its parent domains against the blocklist.<br>
+  /// The TLD on its own is never matched.
+  pub fn contains(
+    &self,
+    host: &str
+  ) -> bool {
+    self.lookup(host).is_some()
+  }
+
+  /// Same as [DomainSet::contains], but returns the entry that matched and its source
+  pub fn lookup(
+    &self,
+    host: &str
+  ) -> Option<DomainMatch<'_>> {
+    let host = host.trim_end_matches('.').to_lowercase();
+    let mut candidate = host.as_str();
+
+    loop {
+      if let Some((entry, source)) = self.domains.get_key_value(candidate) {
+        return Some(DomainMatch {
+          entry,
+          source: source.and_then(|i| self.sources.get(i as usize)).map(|s| &**s)
+        });
+      }
+
+      match candidate.split_once('.') {
+        Some((_, parent)) if parent.contains('.') => candidate = parent,
+        _ => return None
+      }
+    }
+  }
+}
+
+/// Parse a blocklist into domains, accepts hosts-file (`0.0.0.0 evil.com`),
+/// plain-list (`evil.com`) and URL-list (`https://evil.com/login`) formats, even mixed together.<br>
+/// Comments, IP addresses and junk lines are skipped.
+pub fn parse_blocklist(text: &str) -> Vec<String> { text.lines().filter_map(parse_entry).collect() }
+
+/// Normalise a single blocklist line or user input into a bare domain
+pub fn parse_entry(line: &str) -> Option<String> {
+  if line.trim_start().starts_with('!') {
+    return None;
+  }
+
+  let line = line.split('#').next()?.trim();
+  let mut tokens = line.split_whitespace();
+  let first = tokens.next()?;
+
+  // Hosts-file lines lead with the address the domain is sinkholed to
+  let entry = if first.parse::<IpAddr>().is_ok() { tokens.next()? } else { first };
+  let entry = entry.trim_start_matches("||").trim_end_matches('^');
+
+  let host = if entry.contains("://") {
+    Url::parse(entry).ok()?.host_str()?.to_string()
+  } else {
+    let host = entry.split(['/', '?']).next()?;
+    match host.rsplit_once(':') {
+      Some((h, port)) if port.chars().all(|c| c.is_ascii_digit()) => h.to_string(),
+      _ => host.to_string()
+    }
+  };
+
+  let host = host.trim_end_matches('.').to_lowercase();
+  let valid = host.contains('.')
+    && host.parse::<IpAddr>().is_err()
+    && !host.starts_with(['.', '-'])
+    && host.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'));
+
+  if valid { Some(host) } else { None }
+}
+
+/// Hosts the bot's GitHub token is allowed to be sent to when fetching a source
+const GITHUB_HOSTS: [&str; 3] = ["raw.githubusercontent.com", "api.github.com", "github.com"];
+
+/// Whether the source is on GitHub, anything else a moderator added must not see the token
+pub fn is_github_source(url: &str) -> bool {
+  Url::parse(url).is_ok_and(|u| u.scheme() == "https" && u.host_str().is_some_and(|h| GITHUB_HOSTS.contains(&h)))
+}
+
+/// Keeps the in-memory blocklist fresh without touching the message path
+pub struct MaliciousDomains;
+
+#[async_trait]
+impl TaskScheduler for MaliciousDomains {
+  fn name(&self) -> &'static str { "Malicious Domains" }
+
+  fn interval_secs(&self) -> u64 { 300 }
+
+  async fn main_loop(
+    &self,
+    bot_data: Arc<BotData>
+  ) -> Result<(), BotError> {
+    bot_data.automod.refresh_malicious_domains(&bot_data.postgres, false).await
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use {
+    super::{
+      DomainSet,
+      is_github_source,
+      parse_blocklist
+    },
+    std::{
+      hint::black_box,
+      time::Instant
+    }
+  };
+
+  fn build(size: usize) -> DomainSet { DomainSet::new((0..size).map(|i| format!("scam-{i}.example{}.com", i % 97))) }
+
+  #[test]
+  fn lookups_on_a_large_set() {
+    let set = build(200_000);
+
+    assert_eq!(set.len(), 200_000);
+    assert!(set.contains("scam-0.example0.com"));
+    assert!(set.contains("SCAM-199999.example82.com."));
+    assert!(set.contains("cdn.login.scam-42.example42.com"));
+    assert!(!set.contains("scam-42.example43.com"));
+    assert!(!set.contains("example0.com"));
+    assert!(!set.contains("com"));
+  }
+
+  /// Best of a few rounds in nanoseconds per lookup, half of the hosts are listed subdomains and half aren't listed at all
+  fn time_lookups(
+    set: &DomainSet,
+    size: usize
+  ) -> f64 {
+    const LOOKUPS: usize = 200_000;
+    let hosts: Vec<String> = (0..LOOKUPS)
+      .map(|i| match i % 2 {
+        0 => format!("cdn.scam-{}.example{}.com", i % size, i % size % 97),
+        _ => format!("cdn.login.not-listed-{i}.net")
+      })
+      .collect();
+
+    (0..5)
+      .map(|_| {
+        let start = Instant::now();
+        for host in &hosts {
+          black_box(set.contains(black_box(host)));
+        }
+        start.elapsed().as_nanos() as f64 / LOOKUPS as f64
+      })
+      .fold(f64::INFINITY, f64::min)
+  }
+
+  /// Timing is at the mercy of the machine, so it's left out of the regular run.<br>
+  /// Run with `cargo test --release --features automod -- --ignored --nocapture domain_lookup_bench`
+  #[test]
+  #[ignore]
+  fn domain_lookup_bench() {
+    let small = build(1_000);
+    let large = build(200_000);
+
+    let small_ns = time_lookups(&small, 1_000);
+    let large_ns = time_lookups(&large, 200_000);
+
+    println!("DomainSet lookup: {small_ns:.1} ns/op with 1k entries, {large_ns:.1} ns/op with 200k entries");
+
+    // A linear scan would be ~200x slower on the large set, hashing should stay in the same ballpark
+    assert!(large_ns < small_ns * 5.0, "lookup cost grows with the blocklist size");
+  }
+
+  #[test]
+  fn matches_report_their_source() {
+    let set = DomainSet::from_sources(&[
+      ("first", vec!["evil.com".to_string()]),
+      ("second", vec!["evil.com".to_string(), "bad.org".to_string()])
+    ]);
+
+    let found = set.lookup("login.evil.com").unwrap();
+    assert_eq!((found.entry, found.source), ("evil.com", Some("first")));
+    assert_eq!(set.lookup("bad.org").unwrap().source, Some("second"));
+  }
+
+  #[test]
+  fn blocklist_formats() {
+    let parsed = parse_blocklist("# comment\n0.0.0.0 hosts.com\nplain.com\nhttps://url.com/login\n||adblock.com^\n127.0.0.1\n! note");
+
+    assert_eq!(parsed, ["hosts.com", "plain.com", "url.com", "adblock.com"]);
+  }
+
+  #[test]
+  fn github_token_stays_on_github() {
+    assert!(is_github_source("https://raw.githubusercontent.com/nwerosama/FishDB/main/domains.txt"));
+    assert!(is_github_source("https://api.github.com/repos/a/b/contents/list.txt"));
+    assert!(!is_github_source("http://raw.githubusercontent.com/a/b/list.txt"));
+    assert!(!is_github_source("https://raw.githubusercontent.com.evil.com/list.txt"));
+    assert!(!is_github_source("https://evil.com/raw.githubusercontent.com/list.txt"));
+    assert!(!is_github_source("https://gist.github.com/a/b"));
+  }
+}
@@ -0,0 +1,706 @@
+use {
+  super::{
+    AutomodPolicy,
+    AutomodPolicyType,
+    Automoderator,
+    DomainSet,
+    INVITE_KEY,
+    INVITE_UNRESOLVED,
+    INVITE_UNRESOLVED_SECS,
+    Incoming,
+    IncomingFile,
+    backend::{
+      AutomodBackend,
+      MemoryBackend
+    },
+    urls
+  },
+  crate::internals::{
+    config::BINARY_PROPERTIES,
+    word_filter::{
+      MatchMode,
+      WordFilter
+    }
+  },
+  std::{
+    collections::HashSet,
+    sync::{
+      Arc,
+      RwLock as StdRwLock,
+      atomic::AtomicI64
+    }
+  },
+  tokio::sync::RwLock
+};
+
+const NOW: i64 = 1_750_000_000;
+const AUTHOR: u64 = 190_407_856_527_376_384;
+const PARTNER: u64 = 1_023_456_789_012_345_678;
+const BLOCKLIST: &str = "https://lists.example/phishing.txt";
+
+fn automod(backend: MemoryBackend) -> Automoderator { automod_on(Arc::new(backend)) }
+
+/// Keep a handle on the backend to move its clock along
+fn automod_on(backend: Arc<MemoryBackend>) -> Automoderator {
+  Automoderator {
+    policies: Arc::new(RwLock::new(AutomodPolicy::defaults())),
+    pw_list: StdRwLock::new(Arc::new(WordFilter::new(&[("scam".to_string(), MatchMode::Exact)], &[]))),
+    pu_list: StdRwLock::new(Arc::new(vec!["banned.org".to_string()])),
+    md_list: StdRwLock::new(Arc::new(DomainSet::from_sources(&[(BLOCKLIST, vec!["evil.com".to_string()])]))),
+    md_allow: StdRwLock::new(Arc::new(DomainSet::new(["safe.evil.com"]))),
+    exempts: StdRwLock::new(Arc::new(Vec::new())),
+    partners: StdRwLock::new(Arc::new(HashSet::from([PARTNER]))),
+    scam_img: StdRwLock::new(Arc::new(Vec::new())),
+    md_epoch: AtomicI64::new(0),
+    backend
+  }
+}
+
+/// A message from a year old account that joined a month ago
+fn message(content: &str) -> Incoming<'_> {
+  Incoming {
+    author_id: AUTHOR,
+    account_created: NOW - 365 * 86400,
+    joined_at: Some(NOW - 30 * 86400),
+    channel_id: 1,
+    message_id: 1,
+    content,
+    timestamp: NOW,
+    mentions: 0,
+    mention_everyone: false,
+    attachments: Vec::new(),
+    embedded: Vec::new()
+  }
+}
+
+async fn violations(
+  automod: &Automoderator,
+  msg: &Incoming<'_>
+) -> Vec<AutomodPolicyType> {
+  automod
+    .check_violations(msg, false)
+    .await
+    .into_iter()
+    .map(|(p, _)| p.policy_type)
+    .collect()
+}
+
+#[tokio::test]
+async fn clean_message_passes() {
+  let automod = automod(MemoryBackend::default());
+  assert!(violations(&automod, &message("Anyone up for some harvesting tonight?")).await.is_empty());
+}
+
+#[tokio::test]
+async fn anti_spam() {
+  let automod = automod(MemoryBackend::default());
+  let policy = AutomodPolicy::anti_spam();
+
+  for i in 1..=policy.trigger_threshold {
+    let content = format!("message number {i}");
+    let msg = Incoming {
+      message_id: i as u64,
+      timestamp: NOW + i as i64,
+      ..message(&content)
+    };
+    let spam = violations(&automod, &msg).await.contains(&AutomodPolicyType::AntiSpam);
+    assert_eq!(spam, i == policy.trigger_threshold, "message {i}");
+  }
+}
+
+#[tokio::test]
+async fn anti_spam_window_expires() {
+  let automod = automod(MemoryBackend::default());
+  let policy = AutomodPolicy::anti_spam();
+
+  for i in 1..=policy.trigger_threshold {
+    let content = format!("message number {i}");
+    let msg = Incoming {
+      message_id: i as u64,
+      timestamp: NOW + (i * (policy.window_secs + 1)) as i64,
+      ..message(&content)
+    };
+    assert!(violations(&automod, &msg).await.is_empty(), "message {i}");
+  }
+}
+
+#[tokio::test]
+async fn edits_skip_rate_based_policies() {
+  let automod = automod(MemoryBackend::default());
+  let msg = Incoming {
+    mentions: 20,
+    ..message("edited message")
+  };
+
+  for _ in 0..AutomodPolicy::anti_spam().trigger_threshold * 2 {
+    assert!(automod.check_violations(&msg, true).await.is_empty());
+  }
+}
+
+#[tokio::test]
+async fn invite_links() {
+  let automod = automod(
+    MemoryBackend::default()
+      .invite("outsider", 42)
+      .invite("partner", PARTNER)
+      .invite("home", BINARY_PROPERTIES.guild_id)
+  );
+
+  assert_eq!(
+    violations(&automod, &message("join discord.gg/outsider")).await,
+    [AutomodPolicyType::InviteLinks]
+  );
+  assert_eq!(
+    violations(&automod, &message("https://discord.com/invite/nowhere")).await,
+    [AutomodPolicyType::InviteLinks]
+  );
+  assert!(violations(&automod, &message("our partner discord.gg/partner")).await.is_empty());
+  assert!(violations(&automod, &message("invite your friends discord.gg/home")).await.is_empty());
+}
+
+#[tokio::test]
+async fn resolved_invites_are_cached() {
+  let automod = automod(MemoryBackend::default().invite("outsider", 42));
+
+  assert_eq!(automod.resolve_invite("outsider", true).await, Some(42));
+  assert_eq!(
+    automod.backend.get(&format!("{INVITE_KEY}:outsider")).await.unwrap().as_deref(),
+    Some("42")
+  );
+}
+
+#[tokio::test]
+async fn unresolved_invites_are_cached_briefly() {
+  let backend = Arc::new(MemoryBackend::default());
+  let automod = automod_on(Arc::clone(&backend));
+
+  assert_eq!(automod.resolve_invite("nowhere", true).await, None);
+  assert_eq!(
+    backend.get(&format!("{INVITE_KEY}:nowhere")).await.unwrap().as_deref(),
+    Some(INVITE_UNRESOLVED)
+  );
+  assert_eq!(automod.resolve_invite("nowhere", true).await, None);
+
+  backend.advance(INVITE_UNRESOLVED_SECS);
+  assert!(backend.is_empty());
+}
+
+#[tokio::test]
+async fn prohibited_words() {
+  let automod = automod(MemoryBackend::default());
+  let found = automod.check_violations(&message("that's a SCAM"), false).await;
+
+  assert_eq!(found.len(), 1);
+  assert_eq!(found[0].0.policy_type, AutomodPolicyType::ProhibitedWords);
+  assert_eq!(found[0].1.indicator.as_deref(), Some("scam"));
+}
+
+#[tokio::test]
+async fn malicious_links() {
+  let automod = automod(MemoryBackend::default());
+  let found = automod.check_violations(&message("free nitro at https://evil.com/claim"), false).await;
+
+  assert_eq!(found.len(), 1);
+  assert_eq!(found[0].0.policy_type, AutomodPolicyType::MaliciousLinks);
+  assert_eq!(found[0].1.indicator.as_deref(), Some("evil.com"));
+  assert!(found[0].1.reason.contains(BLOCKLIST));
+}
+
+#[tokio::test]
+async fn masked_links() {
+  let automod = automod(MemoryBackend::default());
+
+  for content in [
+    "[discord.com/gifts](https://evil.com/claim)",
+    "[Steam](<https://login.evil.com/>)",
+    "[click here](https://evil.com)"
+  ] {
+    assert_eq!(
+      violations(&automod, &message(content)).await,
+      [AutomodPolicyType::MaliciousLinks],
+      "{content}"
+    );
+  }
+
+  assert_eq!(
+    automod.find_malicious_link("[evil.com](https://example.com)").map(|d| d.indicator),
+    Some(Some("evil.com".to_string()))
+  );
+  assert!(automod.find_malicious_link("[evil](https://example.com)").is_none());
+}
+
+#[test]
+fn subdomains_match_their_parent() {
+  let automod = automod(MemoryBackend::default());
+
+  assert!(automod.find_malicious_link("login.evil.com").is_some());
+  assert!(automod.find_malicious_link("a.b.c.evil.com/x").is_some());
+  assert!(automod.find_malicious_link("notevil.com").is_none());
+  assert!(automod.find_malicious_link("evil.com.au").is_none());
+  // Allowlisted subdomain of a listed domain
+  assert!(automod.find_malicious_link("safe.evil.com").is_none());
+  assert!(automod.find_malicious_link("deeper.safe.evil.com").is_none());
+
+  assert!(automod.find_prohibited_url("cdn.banned.org").is_some());
+  assert!(automod.find_prohibited_url("unbanned.org").is_none());
+}
+
+#[tokio::test]
+async fn prohibited_urls() {
+  let automod = automod(MemoryBackend::default());
+  assert_eq!(
+    violations(&automod, &message("mirror at https://banned.org/download")).await,
+    [AutomodPolicyType::ProhibitedUrls]
+  );
+}
+
+#[tokio::test]
+async fn obfuscated_links() {
+  let automod = automod(MemoryBackend::default());
+
+  for content in [
+    "free nitro at evil[.]com",
+    "hxxps://login . evil . com/gift",
+    "ev\u{200B}il\u{FF0E}com",
+    "[click here](hxxps://evil(.)com)"
+  ] {
+    assert_eq!(
+      violations(&automod, &message(content)).await,
+      [AutomodPolicyType::MaliciousLinks],
+      "{content}"
+    );
+  }
+
+  assert!(automod.find_prohibited_url("mirror at banned[dot]org").is_some());
+}
+
+#[test]
+fn homographs() {
+  let automod = automod(MemoryBackend::default());
+
+  let found = automod.find_malicious_link("\u{435}vil.com/login").unwrap();
+  assert_eq!(found.indicator.as_deref(), Some("evil.com"));
+  assert!(found.reason.contains("`xn--vil-qdd.com` imitates `evil.com`"));
+
+  assert!(automod.find_prohibited_url("b\u{430}nned.org").is_some());
+  assert!(automod.find_prohibited_url("xn--bnned-4ve.org").is_some());
+}
+
+#[tokio::test]
+async fn embedded_links() {
+  let automod = automod(MemoryBackend::default());
+
+  let embed = Incoming {
+    embedded: vec!["Claim your gift", "https://evil.com/claim"],
+    ..message("check this out")
+  };
+  assert_eq!(violations(&automod, &embed).await, [AutomodPolicyType::MaliciousLinks]);
+
+  let forwarded = Incoming {
+    embedded: vec!["mirror at https://banned.org/download"],
+    ..message("")
+  };
+  assert_eq!(violations(&automod, &forwarded).await, [AutomodPolicyType::ProhibitedUrls]);
+}
+
+/// Replace the `\u{...}` escapes of the corpus with the characters they stand for
+fn unescape(line: &str) -> String {
+  let mut out = String::new();
+  let mut rest = line;
+  while let Some(start) = rest.find("\\u{") {
+    out.push_str(&rest[..start]);
+    let end = start + rest[start..].find('}').expect("Unterminated escape");
+    let code = u32::from_str_radix(&rest[start + 3..end], 16).expect("Invalid escape");
+    out.push(char::from_u32(code).expect("Invalid character"));
+    rest = &rest[end + 1..];
+  }
+  out.push_str(rest);
+  out
+}
+
+#[test]
+fn url_corpus() {
+  for line in include_str!("url_corpus.txt").lines().filter(|l| !l.is_empty() && !l.starts_with('#')) {
+    let (input, expected) = line.split_once(" =>").expect("Corpus line is missing `=>`");
+    let expected: Vec<(&str, Option<&str>)> = expected
+      .split(',')
+      .map(str::trim)
+      .filter(|e| !e.is_empty())
+      .map(|e| match e.split_once(" ~ ") {
+        Some((host, lookalike)) => (host, Some(lookalike)),
+        None => (e, None)
+      })
+      .collect();
+
+    let links = urls::extract(&unescape(input));
+    let found: Vec<(&str, Option<&str>)> = links.iter().map(|l| (l.host.as_str(), l.lookalike.as_deref())).collect();
+    assert_eq!(found, expected, "{line}");
+  }
+}
+
+#[test]
+fn masked_hosts_are_flagged() {
+  let links = urls::extract("[discord.com/gifts](https://evil.com/claim)");
+  let masked: Vec<_> = links.iter().map(|l| (l.host.as_str(), l.masked)).collect();
+  assert_eq!(masked, [("discord.com", false), ("evil.com", true)]);
+}
+
+#[tokio::test]
+async fn mass_mention() {
+  let automod = automod(MemoryBackend::default());
+  let policy = AutomodPolicy::mass_mention();
+
+  let half = policy.trigger_threshold / 2;
+  let first = Incoming {
+    mentions: half,
+    ..message("hey you lot")
+  };
+  assert!(violations(&automod, &first).await.is_empty());
+
+  let second = Incoming {
+    message_id: 2,
+    timestamp: NOW + 1,
+    mentions: policy.trigger_threshold - half,
+    ..message("hey you lot, again")
+  };
+  assert_eq!(violations(&automod, &second).await, [AutomodPolicyType::MassMention]);
+}
+
+#[tokio::test]
+async fn duplicate_message() {
+  let automod = automod(MemoryBackend::default());
+  let policy = AutomodPolicy::duplicate_message();
+
+  for channel in 1..=policy.trigger_threshold as u64 {
+    let msg = Incoming {
+      channel_id: channel,
+      message_id: channel,
+      ..message("Check out my new server, it's great!")
+    };
+    let duplicate = violations(&automod, &msg).await.contains(&AutomodPolicyType::DuplicateMessage);
+    assert_eq!(duplicate, channel == policy.trigger_threshold as u64, "channel {channel}");
+  }
+}
+
+#[tokio::test]
+async fn duplicate_message_in_one_channel() {
+  let automod = automod(MemoryBackend::default());
+
+  for id in 1..=AutomodPolicy::duplicate_message().trigger_threshold as u64 {
+    let msg = Incoming {
+      message_id: id,
+      timestamp: NOW + id as i64 * 10,
+      ..message("Check out my new server, it's great!")
+    };
+    assert!(violations(&automod, &msg).await.is_empty());
+  }
+}
+
+#[tokio::test]
+async fn attachments() {
+  let automod = automod(MemoryBackend::default());
+
+  let blocked = Incoming {
+    attachments: vec![IncomingFile {
+      filename: "FreeRobux.EXE",
+      size:     1024,
+      url:      "https://cdn.example/FreeRobux.EXE"
+    }],
+    ..message("")
+  };
+  assert_eq!(violations(&automod, &blocked).await, [AutomodPolicyType::Attachments]);
+
+  let image = Incoming {
+    attachments: vec![IncomingFile {
+      filename: "tractor.png",
+      size:     1024,
+      url:      "https://cdn.example/tractor.png"
+    }],
+    ..message("")
+  };
+  assert!(violations(&automod, &image).await.is_empty());
+}
+
+/// Just enough of a zip for its central directory to list the one file
+fn zip_with(name: &str) -> Vec<u8> {
+  let mut data = vec![0u8; 46];
+  data[..4].copy_from_slice(&0x02014B50u32.to_le_bytes());
+  data[28..30].copy_from_slice(&(name.len() as u16).to_le_bytes());
+  data.extend_from_slice(name.as_bytes());
+
+  // End of central directory, pointing back at the start of the data
+  let mut eocd = [0u8; 22];
+  eocd[..4].copy_from_slice(&0x06054B50u32.to_le_bytes());
+  eocd[10..12].copy_from_slice(&1u16.to_le_bytes());
+  data.extend_from_slice(&eocd);
+  data
+}
+
+#[tokio::test]
+async fn attachments_inside_archives() {
+  const URL: &str = "https://cdn.example/mods.zip";
+  let archive = zip_with("installer/payload.exe");
+  let size = archive.len() as u32;
+  let automod = automod(MemoryBackend::default().file(URL, archive));
+
+  let msg = Incoming {
+    attachments: vec![IncomingFile {
+      filename: "mods.zip",
+      size,
+      url: URL
+    }],
+    ..message("new mod pack")
+  };
+  let found = automod.check_violations(&msg, false).await;
+
+  assert_eq!(found.len(), 1);
+  assert_eq!(found[0].0.policy_type, AutomodPolicyType::Attachments);
+  assert_eq!(found[0].1.indicator.as_deref(), Some("installer/payload.exe"));
+}
+
+#[tokio::test]
+async fn new_account() {
+  let automod = automod(MemoryBackend::default());
+
+  let young_account = Incoming {
+    account_created: NOW - 86400,
+    ..message("have a look https://example.com")
+  };
+  assert_eq!(violations(&automod, &young_account).await, [AutomodPolicyType::NewAccount]);
+
+  let just_joined = Incoming {
+    joined_at: Some(NOW - 60),
+    mentions: 1,
+    ..message("hey")
+  };
+  assert_eq!(violations(&automod, &just_joined).await, [AutomodPolicyType::NewAccount]);
+
+  // Nothing to reach anyone with
+  let plain = Incoming {
+    account_created: NOW - 86400,
+    ..message("hello everyone!")
+  };
+  assert!(violations(&automod, &plain).await.is_empty());
+
+  // Edits don't come with the member, so only the account age counts
+  let edited = Incoming {
+    joined_at: None,
+    ..message("have a look https://example.com")
+  };
+  assert!(violations(&automod, &edited).await.is_empty());
+}
+
+#[test]
+fn profile() {
+  let automod = automod(MemoryBackend::default());
+
+  let (name, detection) = automod
+    .find_profile_violation(&[
+      ("Username", Some("farmer_joe")),
+      ("Display name", Some("scam_lord")),
+      ("Nickname", Some("evil.com"))
+    ])
+    .expect("display name should be caught");
+  assert_eq!(name, "scam_lord");
+  assert!(detection.reason.starts_with("Display name"));
+
+  let (name, _) = automod
+    .find_profile_violation(&[("Username", Some("farmer_joe")), ("Nickname", Some("visit evil.com"))])
+    .expect("nickname should be caught");
+  assert_eq!(name, "visit evil.com");
+
+  assert!(
+    automod
+      .find_profile_violation(&[("Username", Some("farmer_joe")), ("Display name", None), ("Nickname", None)])
+      .is_none()
+  );
+}
+
+#[tokio::test]
+async fn dry_run_leaves_stats_alone() {
+  let backend = Arc::new(MemoryBackend::default().invite("outsider", 42));
+  let automod = automod_on(Arc::clone(&backend));
+  let matches = automod.dry_run("scam at evil.com, join discord.gg/outsider").await;
+
+  let types: Vec<_> = matches.into_iter().map(|(p, _)| p.policy_type).collect();
+  assert_eq!(
+    types,
+    [
+      AutomodPolicyType::InviteLinks,
+      AutomodPolicyType::ProhibitedWords,
+      AutomodPolicyType::MaliciousLinks
+    ]
+  );
+  assert!(backend.is_empty());
+}
+
+#[tokio::test]
+async fn activity_expires_with_its_window() {
+  let backend = Arc::new(MemoryBackend::default());
+  let automod = automod_on(Arc::clone(&backend));
+
+  let msg = Incoming {
+    mentions: 2,
+    ..message("hello everyone, how is the harvest going")
+  };
+  assert!(violations(&automod, &msg).await.is_empty());
+  assert!(!backend.is_empty());
+
+  let longest = [
+    AutomodPolicy::anti_spam(),
+    AutomodPolicy::mass_mention(),
+    AutomodPolicy::duplicate_message()
+  ]
+  .iter()
+  .map(|p| p.window_secs)
+  .max()
+  .unwrap();
+  backend.advance(longest as i64);
+  assert!(backend.is_empty());
+}
+
+#[tokio::test]
+async fn warnings_escalate_at_threshold() {
+  let automod = automod(MemoryBackend::default());
+  let policy = AutomodPolicy::prohibited_words();
+
+  for i in 1..policy.warn_threshold {
+    assert!(!automod.count_warning(AUTHOR, &policy).await.unwrap(), "warning {i}");
+  }
+  assert!(automod.count_warning(AUTHOR, &policy).await.unwrap());
+
+  // The count starts over once the user has been sanctioned
+  assert!(!automod.count_warning(AUTHOR, &policy).await.unwrap());
+}
+
+#[tokio::test]
+async fn warnings_decay_after_quiet_period() {
+  let backend = Arc::new(MemoryBackend::default());
+  let automod = automod_on(Arc::clone(&backend));
+  let policy = AutomodPolicy::prohibited_words();
+  assert_eq!(policy.warn_threshold, 2);
+  let decay = policy.decay_secs as i64;
+
+  assert!(!automod.count_warning(AUTHOR, &policy).await.unwrap());
+  // Just in time, the first warning still counts
+  backend.advance(decay - 1);
+  assert!(automod.count_warning(AUTHOR, &policy).await.unwrap());
+
+  assert!(!automod.count_warning(AUTHOR, &policy).await.unwrap());
+  // Too late, the first warning was forgotten
+  backend.advance(decay);
+  assert!(backend.is_empty());
+  assert!(!automod.count_warning(AUTHOR, &policy).await.unwrap());
+  assert!(automod.count_warning(AUTHOR, &policy).await.unwrap());
+}
+
+#[tokio::test]
+async fn warnings_decay_per_policy() {
+  let backend = Arc::new(MemoryBackend::default());
+  let automod = automod_on(Arc::clone(&backend));
+  let quick = AutomodPolicy {
+    decay_secs: 60,
+    ..AutomodPolicy::prohibited_words()
+  };
+  let slow = AutomodPolicy {
+    decay_secs: 3600,
+    ..AutomodPolicy::prohibited_urls()
+  };
+
+  assert!(!automod.count_warning(AUTHOR, &quick).await.unwrap());
+  assert!(!automod.count_warning(AUTHOR, &slow).await.unwrap());
+
+  backend.advance(600);
+  assert!(!automod.count_warning(AUTHOR, &quick).await.unwrap());
+  assert!(automod.count_warning(AUTHOR, &slow).await.unwrap());
+}
+
+#[tokio::test]
+async fn warnings_are_counted_per_policy() {
+  let automod = automod(MemoryBackend::default());
+  let words = AutomodPolicy::prohibited_words();
+  let urls = AutomodPolicy::prohibited_urls();
+
+  assert!(!automod.count_warning(AUTHOR, &words).await.unwrap());
+  assert!(!automod.count_warning(AUTHOR, &urls).await.unwrap());
+  assert!(automod.count_warning(AUTHOR, &words).await.unwrap());
+}
+
+#[tokio::test]
+async fn delete_only_policies_never_escalate() {
+  let backend = Arc::new(MemoryBackend::default());
+  let automod = automod_on(Arc::clone(&backend));
+  let policy = AutomodPolicy::new_account();
+  assert!(policy.delete_only);
+
+  for _ in 0..10 {
+    assert!(!automod.count_warning(AUTHOR, &policy).await.unwrap());
+  }
+  assert!(backend.is_empty());
+}
+
+#[tokio::test]
+async fn zero_threshold_sanctions_immediately() {
+  let automod = automod(MemoryBackend::default());
+  let policy = AutomodPolicy {
+    warn_threshold: 0,
+    ..AutomodPolicy::prohibited_words()
+  };
+
+  assert!(automod.count_warning(AUTHOR, &policy).await.unwrap());
+  assert!(automod.count_warning(AUTHOR, &policy).await.unwrap());
+}
+
+#[tokio::test]
+async fn shadow_outcome_peeks_without_counting() {
+  let automod = automod(MemoryBackend::default());
+  let policy = AutomodPolicy::prohibited_words();
+
+  let first = automod.next_warning(AUTHOR, &policy).await.unwrap();
+  assert!(!policy.escalates_at(first));
+  assert_eq!(automod.next_warning(AUTHOR, &policy).await.unwrap(), first);
+
+  assert!(!automod.count_warning(AUTHOR, &policy).await.unwrap());
+  assert!(policy.escalates_at(automod.next_warning(AUTHOR, &policy).await.unwrap()));
+  assert!(automod.count_warning(AUTHOR, &policy).await.unwrap());
+}
+
+#[tokio::test]
+async fn edits_still_scan_attachments() {
+  let automod = automod(MemoryBackend::default());
+  let msg = Incoming {
+    attachments: vec![IncomingFile {
+      filename: "FreeRobux.EXE",
+      size:     1024,
+      url:      "https://cdn.example/FreeRobux.EXE"
+    }],
+    ..message("")
+  };
+
+  let found: Vec<_> = automod
+    .check_violations(&msg, true)
+    .await
+    .into_iter()
+    .map(|(p, _)| p.policy_type)
+    .collect();
+  assert_eq!(found, [AutomodPolicyType::Attachments]);
+}
+
+#[tokio::test]
+async fn shadowed_profiles_are_recorded_once() {
+  let automod = automod(MemoryBackend::default());
+  assert!(AutomodPolicy::profile().shadow);
+  let names = [("Username", Some("farmer_joe")), ("Display name", Some("scam_lord"))];
+
+  // Each hit ends up as one recorded event, role and avatar changes afterwards shouldn't add more
+  let recorded = [
+    automod.triage_profile(AUTHOR, Vec::new(), &names).await.unwrap(),
+    automod.triage_profile(AUTHOR, Vec::new(), &names).await.unwrap(),
+    automod.triage_profile(AUTHOR, vec![1], &names).await.unwrap()
+  ];
+  assert_eq!(recorded.iter().flatten().count(), 1);
+
+  // A new name is a new detection
+  let renamed = [("Username", Some("farmer_joe")), ("Display name", Some("scam_king"))];
+  assert!(automod.triage_profile(AUTHOR, Vec::new(), &renamed).await.unwrap().is_some());
+}
@@ -0,0 +1,122 @@
+use {
+  crate::internals::word_filter::{
+    fold_confusable,
+    is_combining,
+    is_invisible
+  },
+  lazy_static::lazy_static,
+  regex::Regex,
+  std::ops::Range,
+  unicode_normalization::UnicodeNormalization
+};
+
+lazy_static! {
+  static ref HOST_REGEX: Regex = Regex::new(r"(?i)(?:https?://)?(?:www\.)?([\p{L}\p{N}][\p{L}\p{N}\p{M}-]*(?:\.[\p{L}\p{N}\p{M}-]+)+)").unwrap();
+  static ref MASKED_URL_REGEX: Regex = Regex::new(r"\[.*?\]\(<?(https?://[^>]+)>?\)").unwrap();
+  /// `hxxp://`, `h**ps://` and `http[:]//`
+  static ref DEFANGED_SCHEME: Regex = Regex::new(r"(?i)\bh(?:xx|\*\*|tt)p(s?)(?:\[:\]|:)//").unwrap();
+  /// `[.]`, `(.)`, `{.}` and the same with `dot`, along with any spacing around them
+  static ref DEFANGED_DOT: Regex = Regex::new(r"(?i)\s*(?:\[\s*(?:\.|dot)\s*\]|\(\s*(?:\.|dot)\s*\)|\{\s*(?:\.|dot)\s*\})\s*").unwrap();
+  static ref SPACED_DOT: Regex = Regex::new(r"\s+\.\s+").unwrap();
+}
+
+/// Host a message links to
+#[derive(Debug, PartialEq)]
+pub struct Link {
+  /// Lowercased and in ASCII, internationalised names are converted to punycode
+  pub host:      String,
+  /// ASCII domain the punycode host is made to look like, if there's one
+  pub lookalike: Option<String>,
+  /// Whether the host is hidden behind the text of a masked link
+  pub masked:    bool
+}
+
+impl Link {
+  /// The host followed by the domain it imitates
+  pub fn candidates(&self) -> impl Iterator<Item = &str> { std::iter::once(self.host.as_str()).chain(self.lookalike.as_deref()) }
+}
+
+/// Zero-width characters, soft hyphens and the like that can split a link without showing up
+fn is_hidden(c: char) -> bool { (is_invisible(c) && !c.is_whitespace()) || matches!(c, '\u{AD}' | '\u{34F}' | '\u{180E}') }
+
+/// Replace every match with a dot, but only if it sits between two characters of a domain
+fn join_labels(
+  text: &str,
+  separator: &Regex
+) -> String {
+  let is_label = |c: Option<char>| c.is_some_and(|c| c.is_alphanumeric());
+  let mut out = String::with_capacity(text.len());
+  let mut last = 0;
+
+  for m in separator.find_iter(text) {
+    out.push_str(&text[last..m.start()]);
+    if is_label(text[..m.start()].chars().next_back()) && is_label(text[m.end()..].chars().next()) {
+      out.push('.');
+    } else {
+      out.push_str(m.as_str());
+    }
+    last = m.end();
+  }
+
+  out.push_str(&text[last..]);
+  out
+}
+
+/// Undo the usual tricks for getting a link past filters:<br>
+/// defanged schemes and dots, dots padded with spaces, fullwidth and ideographic dots, and zero-width characters.
+pub fn deobfuscate(text: &str) -> String {
+  let cleaned: String = text.nfkc().filter(|c| !is_hidden(*c)).map(|c| if c == '。' { '.' } else { c }).collect();
+
+  let cleaned = DEFANGED_SCHEME.replace_all(&cleaned, "http$1://");
+  let cleaned = join_labels(&cleaned, &DEFANGED_DOT);
+  join_labels(&cleaned, &SPACED_DOT)
+}
+
+/// ASCII domain the punycode labels are imitating, folding lookalike letters and stripping accents
+fn lookalike(host: &str) -> Option<String> {
+  if !host.split('.').any(|label| label.starts_with("xn--")) {
+    return None;
+  }
+
+  let (unicode, _) = idna::domain_to_unicode(host);
+  let folded: String = unicode
+    .nfkd()
+    .filter(|c| !is_combining(*c))
+    .flat_map(char::to_lowercase)
+    .map(fold_confusable)
+    .collect();
+
+  (folded.is_ascii() && folded != host).then_some(folded)
+}
+
+/// Every host linked in the text, obfuscated or not, in the order they appear
+pub fn extract(text: &str) -> Vec<Link> {
+  let text = deobfuscate(text);
+  let masked: Vec<Range<usize>> = MASKED_URL_REGEX
+    .captures_iter(&text)
+    .filter_map(|cap| cap.get(1).map(|m| m.range()))
+    .collect();
+
+  let mut links: Vec<Link> = Vec::new();
+  for cap in HOST_REGEX.captures_iter(&text) {
+    let Some(found) = cap.get(1) else {
+      continue;
+    };
+
+    // Anything that can't be a valid domain isn't something a browser would open either
+    let Ok(host) = idna::domain_to_ascii(found.as_str().trim_end_matches('-')) else {
+      continue;
+    };
+    if host.is_empty() || links.iter().any(|l| l.host == host) {
+      continue;
+    }
+
+    links.push(Link {
+      lookalike: lookalike(&host),
+      masked: masked.iter().any(|r| r.contains(&found.start())),
+      host
+    });
+  }
+
+  links
+}
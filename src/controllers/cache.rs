@@ -2,12 +2,16 @@ use {
   crate::internals::utils::token_path,
   bb8_redis::{
     RedisConnectionManager,
-    bb8::Pool,
+    bb8::{
+      Pool,
+      PooledConnection
+    },
     redis::{
       AsyncCommands,
       RedisError,
       RedisResult,
-      cmd
+      cmd,
+      pipe
     }
   },
   std::io::Error,
@@ -82,6 +86,14 @@ impl RedisController {
     }
   }
 
+  async fn connection(&self) -> RedisResult<PooledConnection<'_, RedisConnectionManager>> {
+    self
+      .pool
+      .get()
+      .await
+      .map_err(|_| RedisError::from(Error::other("Failed to get a connection!")))
+  }
+
   /// Get a key from the cache
   pub async fn get(
     &self,
@@ -115,4 +127,49 @@ impl RedisController {
   ) -> RedisResult<()> {
     with_conn!(self, del(key))
   }
+
+  /// Increment the counter and push its expiration back, returns the new count
+  pub async fn incr_expire(
+    &self,
+    key: &str,
+    seconds: i64
+  ) -> RedisResult<i64> {
+    let mut conn = self.connection().await?;
+    let (count,): (i64,) = pipe().atomic().incr(key, 1).expire(key, seconds).ignore().query_async(&mut *conn).await?;
+    Ok(count)
+  }
+
+  /// Add the member to the sorted set and drop everything scored below `since`, the set expires after `seconds`.<br>
+  /// Returns what's left in the set, lowest score first
+  pub async fn zadd_window(
+    &self,
+    key: &str,
+    score: i64,
+    member: &str,
+    since: i64,
+    seconds: i64
+  ) -> RedisResult<Vec<String>> {
+    let mut conn = self.connection().await?;
+    let (members,): (Vec<String>,) = pipe()
+      .atomic()
+      .zrembyscore(key, "-inf", format!("({since}"))
+      .ignore()
+      .zadd(key, member, score)
+      .ignore()
+      .zrangebyscore(key, since, "+inf")
+      .expire(key, seconds)
+      .ignore()
+      .query_async(&mut *conn)
+      .await?;
+    Ok(members)
+  }
+
+  /// Members of the sorted set scored `since` or higher, lowest score first
+  pub async fn zrange_since(
+    &self,
+    key: &str,
+    since: i64
+  ) -> RedisResult<Vec<String>> {
+    with_conn!(self, zrangebyscore(key, since, "+inf"))
+  }
 }
@@ -1,14 +1,56 @@
+mod automod_allowlist;
+pub use automod_allowlist::AutomodAllowlist;
+
+mod automod_feedback;
+pub use automod_feedback::AutomodFeedback;
+
+mod automod_events;
+pub use automod_events::AutomodEvents;
+
+mod automod_exemptions;
+pub use automod_exemptions::AutomodExemptions;
+
+mod automod_partners;
+pub use automod_partners::AutomodPartners;
+
+mod automod_policies;
+pub use automod_policies::AutomodPolicies;
+
+mod automod_sources;
+pub use automod_sources::AutomodSources;
+
+mod mp_subscriptions;
+pub use mp_subscriptions::MpSubscriptions;
+
 mod mpservers;
 pub use mpservers::MpServers;
 
+mod player_samples;
+pub use player_samples::{
+  HourlyPlayers,
+  PlayerSamples
+};
+
+mod player_sessions;
+pub use player_sessions::{
+  PlayerSessions,
+  PlayerTotal
+};
+
 mod prohibited_words;
 pub use prohibited_words::ProhibitedWords;
 
 mod sanctions;
 pub use sanctions::Sanctions;
 
+mod scam_images;
+pub use scam_images::ScamImages;
+
 mod settings;
-pub use settings::Settings;
+pub use settings::{
+  PlayerRetention,
+  Settings
+};
 
 mod webhooks;
 pub use webhooks::Webhooks;
@@ -16,6 +58,9 @@ pub use webhooks::Webhooks;
 mod prohibited_urls;
 pub use prohibited_urls::ProhibitedUrls;
 
+mod word_allowlist;
+pub use word_allowlist::WordAllowlist;
+
 use {
   regex::Regex,
   std::{
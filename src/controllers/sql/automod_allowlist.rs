@@ -0,0 +1,68 @@
+use super::{
+  DAG_SQL,
+  QUERY_FAILED
+};
+
+use sqlx::{
+  FromRow,
+  PgPool,
+  Result
+};
+
+#[derive(Clone, FromRow, PartialEq)]
+pub struct AutomodAllowlist {
+  pub domain: String
+}
+
+impl AutomodAllowlist {
+  pub async fn get_domains(pool: &PgPool) -> Result<Vec<AutomodAllowlist>> {
+    let rows = match sqlx::query_as::<_, AutomodAllowlist>("SELECT domain FROM automod_allowlist")
+      .fetch_all(pool)
+      .await
+    {
+      Ok(r) => r,
+      Err(e) => {
+        eprintln!("{DAG_SQL}[Database:AutomodAllowlist:get_domains:Error] {QUERY_FAILED}\n{e}");
+        return Err(e)
+      }
+    };
+
+    Ok(rows)
+  }
+
+  pub async fn add_domain(
+    pool: &PgPool,
+    domain: &str
+  ) -> Result<bool> {
+    let q = sqlx::query("INSERT INTO automod_allowlist (domain) VALUES ($1) ON CONFLICT DO NOTHING")
+      .bind(domain)
+      .execute(pool)
+      .await;
+
+    match q {
+      Ok(r) => Ok(r.rows_affected() > 0),
+      Err(e) => {
+        eprintln!("{DAG_SQL}[Database:AutomodAllowlist:add_domain:Error] {QUERY_FAILED}\n{e}");
+        Err(e)
+      }
+    }
+  }
+
+  pub async fn remove_domain(
+    pool: &PgPool,
+    domain: &str
+  ) -> Result<bool> {
+    let q = sqlx::query("DELETE FROM automod_allowlist WHERE domain = $1")
+      .bind(domain)
+      .execute(pool)
+      .await;
+
+    match q {
+      Ok(r) => Ok(r.rows_affected() > 0),
+      Err(e) => {
+        eprintln!("{DAG_SQL}[Database:AutomodAllowlist:remove_domain:Error] {QUERY_FAILED}\n{e}");
+        Err(e)
+      }
+    }
+  }
+}
@@ -0,0 +1,141 @@
+use super::{
+  DAG_SQL,
+  QUERY_FAILED
+};
+
+use sqlx::{
+  FromRow,
+  PgPool,
+  Result,
+  Row
+};
+
+#[derive(Clone, FromRow)]
+pub struct AutomodEvents {
+  pub policy_type: String,
+  pub user_id:     i64,
+  pub channel_id:  Option<i64>,
+  pub action:      String,
+  /// Only set when the detection led to a sanction
+  pub case_id:     Option<i32>,
+  pub indicator:   Option<String>,
+  pub timestamp:   i64
+}
+
+/// Detections of a single policy over a period
+pub struct PolicyCount {
+  pub policy_type: String,
+  pub total:       i64,
+  /// Detections that ended in a sanction rather than just a deleted message
+  pub sanctioned:  i64
+}
+
+impl AutomodEvents {
+  pub async fn record(
+    &self,
+    pool: &PgPool
+  ) -> Result<()> {
+    let q = sqlx::query(
+      "INSERT INTO automod_events (policy_type, user_id, channel_id, action, case_id, indicator, timestamp) VALUES ($1, $2, $3, $4, $5, $6, $7)"
+    )
+    .bind(&self.policy_type)
+    .bind(self.user_id)
+    .bind(self.channel_id)
+    .bind(&self.action)
+    .bind(self.case_id)
+    .bind(&self.indicator)
+    .bind(self.timestamp)
+    .execute(pool)
+    .await;
+
+    if let Err(e) = q {
+      eprintln!("{DAG_SQL}[Database:AutomodEvents:record:Error] {QUERY_FAILED}\n{e}");
+      return Err(e);
+    }
+
+    Ok(())
+  }
+
+  pub async fn counts_per_policy(
+    pool: &PgPool,
+    since: i64
+  ) -> Result<Vec<PolicyCount>> {
+    let rows = match sqlx::query(
+      "SELECT policy_type, COUNT(*) AS total, COUNT(*) FILTER (WHERE action NOT IN ('Delete', 'Shadow')) AS sanctioned
+      FROM automod_events WHERE timestamp >= $1
+      GROUP BY policy_type ORDER BY total DESC"
+    )
+    .bind(since)
+    .fetch_all(pool)
+    .await
+    {
+      Ok(r) => r,
+      Err(e) => {
+        eprintln!("{DAG_SQL}[Database:AutomodEvents:counts_per_policy:Error] {QUERY_FAILED}\n{e}");
+        return Err(e)
+      }
+    };
+
+    Ok(
+      rows
+        .into_iter()
+        .map(|r| PolicyCount {
+          policy_type: r.get("policy_type"),
+          total:       r.get("total"),
+          sanctioned:  r.get("sanctioned")
+        })
+        .collect()
+    )
+  }
+
+  /// Most common indicators for the given policies
+  pub async fn top_indicators(
+    pool: &PgPool,
+    since: i64,
+    policy_types: &[&str],
+    limit: i64
+  ) -> Result<Vec<(String, i64)>> {
+    let rows = match sqlx::query(
+      "SELECT indicator, COUNT(*) AS hits FROM automod_events
+      WHERE timestamp >= $1 AND policy_type = ANY($2) AND indicator IS NOT NULL
+      GROUP BY indicator ORDER BY hits DESC LIMIT $3"
+    )
+    .bind(since)
+    .bind(policy_types)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+    {
+      Ok(r) => r,
+      Err(e) => {
+        eprintln!("{DAG_SQL}[Database:AutomodEvents:top_indicators:Error] {QUERY_FAILED}\n{e}");
+        return Err(e)
+      }
+    };
+
+    Ok(rows.into_iter().map(|r| (r.get("indicator"), r.get("hits"))).collect())
+  }
+
+  /// Detections per day, keyed by days since the Unix epoch
+  pub async fn daily_counts(
+    pool: &PgPool,
+    since: i64
+  ) -> Result<Vec<(i64, i64)>> {
+    let rows = match sqlx::query(
+      "SELECT timestamp / 86400 AS day, COUNT(*) AS hits FROM automod_events
+      WHERE timestamp >= $1 GROUP BY day ORDER BY day"
+    )
+    .bind(since)
+    .fetch_all(pool)
+    .await
+    {
+      Ok(r) => r,
+      Err(e) => {
+        eprintln!("{DAG_SQL}[Database:AutomodEvents:daily_counts:Error] {QUERY_FAILED}\n{e}");
+        return Err(e)
+      }
+    };
+
+    Ok(rows.into_iter().map(|r| (r.get("day"), r.get("hits"))).collect())
+  }
+}
@@ -0,0 +1,74 @@
+use super::{
+  DAG_SQL,
+  QUERY_FAILED
+};
+
+use sqlx::{
+  FromRow,
+  PgPool,
+  Result
+};
+
+#[derive(Clone, FromRow)]
+pub struct AutomodExemptions {
+  pub policy_type: String,
+  pub target_type: String,
+  pub target_id:   i64
+}
+
+impl AutomodExemptions {
+  pub async fn get_exemptions(pool: &PgPool) -> Result<Vec<AutomodExemptions>> {
+    let rows = match sqlx::query_as::<_, AutomodExemptions>("SELECT * FROM automod_exemptions")
+      .fetch_all(pool)
+      .await
+    {
+      Ok(r) => r,
+      Err(e) => {
+        eprintln!("{DAG_SQL}[Database:AutomodExemptions:get_exemptions:Error] {QUERY_FAILED}\n{e}");
+        return Err(e)
+      }
+    };
+
+    Ok(rows)
+  }
+
+  pub async fn add(
+    &self,
+    pool: &PgPool
+  ) -> Result<bool> {
+    let q = sqlx::query("INSERT INTO automod_exemptions (policy_type, target_type, target_id) VALUES ($1, $2, $3) ON CONFLICT DO NOTHING")
+      .bind(&self.policy_type)
+      .bind(&self.target_type)
+      .bind(self.target_id)
+      .execute(pool)
+      .await;
+
+    match q {
+      Ok(r) => Ok(r.rows_affected() > 0),
+      Err(e) => {
+        eprintln!("{DAG_SQL}[Database:AutomodExemptions:add:Error] {QUERY_FAILED}\n{e}");
+        Err(e)
+      }
+    }
+  }
+
+  pub async fn remove(
+    &self,
+    pool: &PgPool
+  ) -> Result<bool> {
+    let q = sqlx::query("DELETE FROM automod_exemptions WHERE policy_type = $1 AND target_type = $2 AND target_id = $3")
+      .bind(&self.policy_type)
+      .bind(&self.target_type)
+      .bind(self.target_id)
+      .execute(pool)
+      .await;
+
+    match q {
+      Ok(r) => Ok(r.rows_affected() > 0),
+      Err(e) => {
+        eprintln!("{DAG_SQL}[Database:AutomodExemptions:remove:Error] {QUERY_FAILED}\n{e}");
+        Err(e)
+      }
+    }
+  }
+}
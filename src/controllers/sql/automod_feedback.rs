@@ -0,0 +1,72 @@
+use super::{
+  DAG_SQL,
+  QUERY_FAILED
+};
+
+use sqlx::{
+  FromRow,
+  PgPool,
+  Result,
+  Row
+};
+
+/// Staff pardon on an automod case, i.e. a false positive
+#[derive(Clone, FromRow)]
+pub struct AutomodFeedback {
+  pub case_id:      i32,
+  pub policy_type:  String,
+  pub user_id:      i64,
+  pub moderator_id: i64,
+  pub timestamp:    i64
+}
+
+impl AutomodFeedback {
+  pub async fn record(
+    &self,
+    pool: &PgPool
+  ) -> Result<bool> {
+    let q = sqlx::query(
+      "INSERT INTO automod_feedback (case_id, policy_type, user_id, moderator_id, timestamp) VALUES ($1, $2, $3, $4, $5) ON CONFLICT DO NOTHING"
+    )
+    .bind(self.case_id)
+    .bind(&self.policy_type)
+    .bind(self.user_id)
+    .bind(self.moderator_id)
+    .bind(self.timestamp)
+    .execute(pool)
+    .await;
+
+    match q {
+      Ok(r) => Ok(r.rows_affected() > 0),
+      Err(e) => {
+        eprintln!("{DAG_SQL}[Database:AutomodFeedback:record:Error] {QUERY_FAILED}\n{e}");
+        Err(e)
+      }
+    }
+  }
+
+  /// Number of pardoned cases for each policy, out of the ones detected since the timestamp.<br>
+  /// Goes by when the case was detected rather than pardoned, so it lines up with the sanctioned count.
+  pub async fn pardons_per_policy(
+    pool: &PgPool,
+    since: i64
+  ) -> Result<Vec<(String, i64)>> {
+    let rows = match sqlx::query(
+      "SELECT e.policy_type, COUNT(*) AS pardons FROM automod_feedback f
+      JOIN automod_events e ON e.case_id = f.case_id
+      WHERE e.timestamp >= $1 GROUP BY e.policy_type"
+    )
+    .bind(since)
+    .fetch_all(pool)
+    .await
+    {
+      Ok(r) => r,
+      Err(e) => {
+        eprintln!("{DAG_SQL}[Database:AutomodFeedback:pardons_per_policy:Error] {QUERY_FAILED}\n{e}");
+        return Err(e)
+      }
+    };
+
+    Ok(rows.into_iter().map(|r| (r.get("policy_type"), r.get("pardons"))).collect())
+  }
+}
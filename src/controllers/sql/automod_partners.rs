@@ -0,0 +1,72 @@
+use super::{
+  DAG_SQL,
+  QUERY_FAILED
+};
+
+use sqlx::{
+  FromRow,
+  PgPool,
+  Result
+};
+
+#[derive(Clone, FromRow)]
+pub struct AutomodPartners {
+  pub guild_id: i64,
+  pub name:     String,
+  pub added_by: i64
+}
+
+impl AutomodPartners {
+  pub async fn get_partners(pool: &PgPool) -> Result<Vec<AutomodPartners>> {
+    let rows = match sqlx::query_as::<_, AutomodPartners>("SELECT * FROM automod_partners ORDER BY name")
+      .fetch_all(pool)
+      .await
+    {
+      Ok(r) => r,
+      Err(e) => {
+        eprintln!("{DAG_SQL}[Database:AutomodPartners:get_partners:Error] {QUERY_FAILED}\n{e}");
+        return Err(e)
+      }
+    };
+
+    Ok(rows)
+  }
+
+  pub async fn add(
+    &self,
+    pool: &PgPool
+  ) -> Result<bool> {
+    let q = sqlx::query("INSERT INTO automod_partners (guild_id, name, added_by) VALUES ($1, $2, $3) ON CONFLICT DO NOTHING")
+      .bind(self.guild_id)
+      .bind(&self.name)
+      .bind(self.added_by)
+      .execute(pool)
+      .await;
+
+    match q {
+      Ok(r) => Ok(r.rows_affected() > 0),
+      Err(e) => {
+        eprintln!("{DAG_SQL}[Database:AutomodPartners:add:Error] {QUERY_FAILED}\n{e}");
+        Err(e)
+      }
+    }
+  }
+
+  pub async fn remove(
+    pool: &PgPool,
+    guild_id: i64
+  ) -> Result<bool> {
+    let q = sqlx::query("DELETE FROM automod_partners WHERE guild_id = $1")
+      .bind(guild_id)
+      .execute(pool)
+      .await;
+
+    match q {
+      Ok(r) => Ok(r.rows_affected() > 0),
+      Err(e) => {
+        eprintln!("{DAG_SQL}[Database:AutomodPartners:remove:Error] {QUERY_FAILED}\n{e}");
+        Err(e)
+      }
+    }
+  }
+}
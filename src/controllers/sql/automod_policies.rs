@@ -0,0 +1,88 @@
+use super::{
+  DAG_SQL,
+  QUERY_FAILED
+};
+
+use sqlx::{
+  FromRow,
+  PgPool,
+  Result
+};
+
+#[derive(Debug, Clone, FromRow)]
+pub struct AutomodPolicies {
+  pub policy_type:       String,
+  pub enabled:           bool,
+  pub action:            String,
+  pub reason:            String,
+  pub warn_threshold:    i32,
+  pub mute_duration:     Option<i64>,
+  pub trigger_threshold: Option<i32>,
+  pub window_secs:       Option<i32>,
+  pub decay_secs:        Option<i32>,
+  pub account_age_days:  Option<i32>,
+  pub member_age_secs:   Option<i32>,
+  pub delete_only:       bool,
+  pub shadow:            bool
+}
+
+impl AutomodPolicies {
+  pub async fn get_policies(pool: &PgPool) -> Result<Vec<Self>> {
+    let rows = match sqlx::query_as::<_, Self>("SELECT * FROM automod_policies").fetch_all(pool).await {
+      Ok(r) => r,
+      Err(e) => {
+        eprintln!("{DAG_SQL}[Database:AutomodPolicies:get_policies:Error] {QUERY_FAILED}\n{e}");
+        return Err(e)
+      }
+    };
+
+    Ok(rows)
+  }
+
+  /// Insert the policy or overwrite the existing one
+  pub async fn upsert(
+    &self,
+    pool: &PgPool
+  ) -> Result<()> {
+    let q = sqlx::query(
+      "INSERT INTO automod_policies (policy_type, enabled, action, reason, warn_threshold, mute_duration, trigger_threshold, window_secs, \
+       account_age_days, member_age_secs, shadow, decay_secs, delete_only)
+      VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+      ON CONFLICT (policy_type) DO UPDATE SET
+        enabled = EXCLUDED.enabled,
+        action = EXCLUDED.action,
+        reason = EXCLUDED.reason,
+        warn_threshold = EXCLUDED.warn_threshold,
+        mute_duration = EXCLUDED.mute_duration,
+        trigger_threshold = EXCLUDED.trigger_threshold,
+        window_secs = EXCLUDED.window_secs,
+        account_age_days = EXCLUDED.account_age_days,
+        member_age_secs = EXCLUDED.member_age_secs,
+        shadow = EXCLUDED.shadow,
+        decay_secs = EXCLUDED.decay_secs,
+        delete_only = EXCLUDED.delete_only"
+    )
+    .bind(&self.policy_type)
+    .bind(self.enabled)
+    .bind(&self.action)
+    .bind(&self.reason)
+    .bind(self.warn_threshold)
+    .bind(self.mute_duration)
+    .bind(self.trigger_threshold)
+    .bind(self.window_secs)
+    .bind(self.account_age_days)
+    .bind(self.member_age_secs)
+    .bind(self.shadow)
+    .bind(self.decay_secs)
+    .bind(self.delete_only)
+    .execute(pool)
+    .await;
+
+    if let Err(e) = q {
+      eprintln!("{DAG_SQL}[Database:AutomodPolicies:upsert:Error] {QUERY_FAILED}\n{e}");
+      return Err(e);
+    }
+
+    Ok(())
+  }
+}
@@ -0,0 +1,113 @@
+use super::{
+  DAG_SQL,
+  QUERY_FAILED
+};
+
+use sqlx::{
+  FromRow,
+  PgPool,
+  Result
+};
+
+#[derive(Clone, FromRow)]
+pub struct AutomodSources {
+  pub url:          String,
+  pub last_success: Option<i64>,
+  pub entry_count:  i32,
+  pub last_error:   Option<String>
+}
+
+impl AutomodSources {
+  pub async fn get_sources(pool: &PgPool) -> Result<Vec<AutomodSources>> {
+    let rows = match sqlx::query_as::<_, AutomodSources>("SELECT * FROM automod_sources ORDER BY url")
+      .fetch_all(pool)
+      .await
+    {
+      Ok(r) => r,
+      Err(e) => {
+        eprintln!("{DAG_SQL}[Database:AutomodSources:get_sources:Error] {QUERY_FAILED}\n{e}");
+        return Err(e)
+      }
+    };
+
+    Ok(rows)
+  }
+
+  pub async fn add_source(
+    pool: &PgPool,
+    url: &str
+  ) -> Result<bool> {
+    let q = sqlx::query("INSERT INTO automod_sources (url) VALUES ($1) ON CONFLICT DO NOTHING")
+      .bind(url)
+      .execute(pool)
+      .await;
+
+    match q {
+      Ok(r) => Ok(r.rows_affected() > 0),
+      Err(e) => {
+        eprintln!("{DAG_SQL}[Database:AutomodSources:add_source:Error] {QUERY_FAILED}\n{e}");
+        Err(e)
+      }
+    }
+  }
+
+  pub async fn remove_source(
+    pool: &PgPool,
+    url: &str
+  ) -> Result<bool> {
+    let q = sqlx::query("DELETE FROM automod_sources WHERE url = $1").bind(url).execute(pool).await;
+
+    match q {
+      Ok(r) => Ok(r.rows_affected() > 0),
+      Err(e) => {
+        eprintln!("{DAG_SQL}[Database:AutomodSources:remove_source:Error] {QUERY_FAILED}\n{e}");
+        Err(e)
+      }
+    }
+  }
+
+  /// Record a successful fetch, the last error is kept around until the next failure overwrites it
+  pub async fn record_success(
+    pool: &PgPool,
+    url: &str,
+    entry_count: i32,
+    timestamp: i64
+  ) -> Result<()> {
+    match sqlx::query("UPDATE automod_sources SET last_success = $2, entry_count = $3 WHERE url = $1")
+      .bind(url)
+      .bind(timestamp)
+      .bind(entry_count)
+      .execute(pool)
+      .await
+    {
+      Ok(_) => (),
+      Err(e) => {
+        eprintln!("{DAG_SQL}[Database:AutomodSources:record_success:Error] {QUERY_FAILED}\n{e}");
+        return Err(e)
+      }
+    };
+
+    Ok(())
+  }
+
+  pub async fn record_failure(
+    pool: &PgPool,
+    url: &str,
+    error: &str
+  ) -> Result<()> {
+    match sqlx::query("UPDATE automod_sources SET last_error = $2 WHERE url = $1")
+      .bind(url)
+      .bind(error)
+      .execute(pool)
+      .await
+    {
+      Ok(_) => (),
+      Err(e) => {
+        eprintln!("{DAG_SQL}[Database:AutomodSources:record_failure:Error] {QUERY_FAILED}\n{e}");
+        return Err(e)
+      }
+    };
+
+    Ok(())
+  }
+}
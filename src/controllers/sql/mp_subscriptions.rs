@@ -0,0 +1,139 @@
+use super::{
+  DAG_SQL,
+  QUERY_FAILED
+};
+
+use sqlx::{
+  FromRow,
+  PgPool,
+  Result
+};
+
+/// Member asking to be DMed about a player or a server
+#[derive(Clone, FromRow)]
+pub struct MpSubscriptions {
+  pub id:            i32,
+  pub user_id:       i64,
+  pub kind:          String,
+  pub target:        String,
+  pub free_slots:    Option<i32>,
+  pub last_notified: i64
+}
+
+impl MpSubscriptions {
+  pub const PLAYER: &'static str = "player";
+  pub const SERVER: &'static str = "server";
+
+  /// Subscribe, or update the slot threshold if they're already subscribed
+  pub async fn create(
+    pool: &PgPool,
+    user_id: i64,
+    kind: &str,
+    target: &str,
+    free_slots: Option<i32>
+  ) -> Result<()> {
+    let q = sqlx::query(
+      "INSERT INTO mp_subscriptions (user_id, kind, target, free_slots) VALUES ($1, $2, $3, $4)
+      ON CONFLICT (user_id, kind, LOWER(target)) DO UPDATE SET free_slots = EXCLUDED.free_slots"
+    )
+    .bind(user_id)
+    .bind(kind)
+    .bind(target)
+    .bind(free_slots)
+    .execute(pool)
+    .await;
+
+    if let Err(e) = q {
+      eprintln!("{DAG_SQL}[Database:MpSubscriptions:create:Error] {QUERY_FAILED}\n{e}");
+      return Err(e);
+    }
+
+    Ok(())
+  }
+
+  pub async fn delete(
+    pool: &PgPool,
+    user_id: i64,
+    kind: &str,
+    target: &str
+  ) -> Result<bool> {
+    let q = sqlx::query("DELETE FROM mp_subscriptions WHERE user_id = $1 AND kind = $2 AND LOWER(target) = LOWER($3)")
+      .bind(user_id)
+      .bind(kind)
+      .bind(target)
+      .execute(pool)
+      .await;
+
+    match q {
+      Ok(r) => Ok(r.rows_affected() > 0),
+      Err(e) => {
+        eprintln!("{DAG_SQL}[Database:MpSubscriptions:delete:Error] {QUERY_FAILED}\n{e}");
+        Err(e)
+      }
+    }
+  }
+
+  pub async fn get_for_user(
+    pool: &PgPool,
+    user_id: i64
+  ) -> Result<Vec<Self>> {
+    let q = sqlx::query_as::<_, Self>("SELECT * FROM mp_subscriptions WHERE user_id = $1 ORDER BY kind, target")
+      .bind(user_id)
+      .fetch_all(pool)
+      .await;
+
+    match q {
+      Ok(rows) => Ok(rows),
+      Err(e) => {
+        eprintln!("{DAG_SQL}[Database:MpSubscriptions:get_for_user:Error] {QUERY_FAILED}\n{e}");
+        Err(e)
+      }
+    }
+  }
+
+  /// Subscriptions of the given kind watching any of the targets, names are matched regardless of case
+  pub async fn get_for_targets(
+    pool: &PgPool,
+    kind: &str,
+    targets: &[String]
+  ) -> Result<Vec<Self>> {
+    let lowered: Vec<String> = targets.iter().map(|t| t.to_lowercase()).collect();
+    let q = sqlx::query_as::<_, Self>("SELECT * FROM mp_subscriptions WHERE kind = $1 AND LOWER(target) = ANY($2)")
+      .bind(kind)
+      .bind(lowered)
+      .fetch_all(pool)
+      .await;
+
+    match q {
+      Ok(rows) => Ok(rows),
+      Err(e) => {
+        eprintln!("{DAG_SQL}[Database:MpSubscriptions:get_for_targets:Error] {QUERY_FAILED}\n{e}");
+        Err(e)
+      }
+    }
+  }
+
+  /// Mark the subscription as notified, unless it already was within the cooldown.<br>
+  /// Returns whether the notification should go out, so concurrent refreshes can't both send one.
+  pub async fn claim(
+    &self,
+    pool: &PgPool,
+    now: i64,
+    cooldown_secs: i64
+  ) -> Result<bool> {
+    let q = sqlx::query("UPDATE mp_subscriptions SET last_notified = $2 WHERE id = $1 AND last_notified <= $2 - $3")
+      .bind(self.id)
+      .bind(now)
+      .bind(cooldown_secs)
+      .execute(pool)
+      .await;
+
+    match q {
+      Ok(r) => Ok(r.rows_affected() > 0),
+      Err(e) => {
+        eprintln!("{DAG_SQL}[Database:MpSubscriptions:claim:Error] {QUERY_FAILED}\n{e}");
+        Err(e)
+      }
+    }
+  }
+}
@@ -31,7 +31,10 @@ pub struct MpServers {
   pub ip:            String,
   pub code:          String,
   pub game_password: String,
-  pub peak_players:  i32
+  pub peak_players:  i32,
+  /// Whether players joining and leaving are posted into the server's thread
+  #[serde(default)]
+  pub join_feed:     bool
 }
 
 impl MpServers {
@@ -49,7 +52,8 @@ impl MpServers {
             ip:            row.get("ip"),
             code:          row.get("code"),
             game_password: row.get("game_password"),
-            peak_players:  row.get("peak_players")
+            peak_players:  row.get("peak_players"),
+            join_feed:     row.get("join_feed")
           })
         }
       },
@@ -75,7 +79,8 @@ impl MpServers {
         ip:            row.get("ip"),
         code:          row.get("code"),
         game_password: row.get("game_password"),
-        peak_players:  row.get("peak_players")
+        peak_players:  row.get("peak_players"),
+        join_feed:     row.get("join_feed")
       })),
       Err(e) => {
         eprintln!("{DAG_SQL}[Database:MpServers:get_server:Error] {QUERY_FAILED}\n{e}");
@@ -280,16 +285,18 @@ impl MpServers {
     is_active: bool,
     ip: String,
     code: String,
-    game_password: String
+    game_password: String,
+    join_feed: bool
   ) -> Result<bool> {
     let q = sqlx::query(
-      "UPDATE mpservers SET is_active = $1, ip = $2, code = $3, game_password = $4
-      WHERE name = $5"
+      "UPDATE mpservers SET is_active = $1, ip = $2, code = $3, game_password = $4, join_feed = $5
+      WHERE name = $6"
     )
     .bind(is_active)
     .bind(ip)
     .bind(code)
     .bind(game_password)
+    .bind(join_feed)
     .bind(name)
     .execute(pool)
     .await;
@@ -0,0 +1,166 @@
+use super::{
+  DAG_SQL,
+  PlayerRetention,
+  QUERY_FAILED
+};
+
+use sqlx::{
+  FromRow,
+  PgPool,
+  Result
+};
+
+const HOUR_SECS: i64 = 3600;
+const DAY_SECS: i64 = 86400;
+
+/// Player count reported by Monica on a single refresh
+#[derive(Clone, FromRow)]
+pub struct PlayerSamples {
+  pub server_name: String,
+  pub timestamp:   i64,
+  pub players:     i32
+}
+
+/// Player counts over one hour of a server's history
+#[derive(Clone, FromRow)]
+pub struct HourlyPlayers {
+  /// Unix timestamp of the start of the hour
+  pub bucket:       i64,
+  pub avg_players:  f32,
+  pub peak_players: i32,
+  pub samples:      i32
+}
+
+impl PlayerSamples {
+  pub async fn record(
+    &self,
+    pool: &PgPool
+  ) -> Result<()> {
+    let q = sqlx::query("INSERT INTO player_samples (server_name, timestamp, players) VALUES ($1, $2, $3) ON CONFLICT DO NOTHING")
+      .bind(&self.server_name)
+      .bind(self.timestamp)
+      .bind(self.players)
+      .execute(pool)
+      .await;
+
+    if let Err(e) = q {
+      eprintln!("{DAG_SQL}[Database:PlayerSamples:record:Error] {QUERY_FAILED}\n{e}");
+      return Err(e);
+    }
+
+    Ok(())
+  }
+
+  /// Roll the raw samples up into hourly and daily aggregates.<br>
+  /// Only periods that have ended before `now` are written, and each run picks up from
+  /// the latest period already stored so a restart doesn't leave gaps.
+  pub async fn downsample(
+    pool: &PgPool,
+    now: i64
+  ) -> Result<()> {
+    let hourly = sqlx::query(
+      "INSERT INTO player_samples_hourly (server_name, bucket, avg_players, peak_players, samples)
+      SELECT s.server_name, s.timestamp - s.timestamp % $2 AS bucket, AVG(s.players)::REAL, MAX(s.players), COUNT(*)::INT
+      FROM player_samples s
+      WHERE s.timestamp < $1 - $1 % $2
+        AND s.timestamp >= COALESCE((SELECT MAX(h.bucket) FROM player_samples_hourly h WHERE h.server_name = s.server_name), 0)
+      GROUP BY s.server_name, bucket
+      ON CONFLICT (server_name, bucket) DO UPDATE SET
+        avg_players = EXCLUDED.avg_players, peak_players = EXCLUDED.peak_players, samples = EXCLUDED.samples"
+    )
+    .bind(now)
+    .bind(HOUR_SECS)
+    .execute(pool)
+    .await;
+
+    if let Err(e) = hourly {
+      eprintln!("{DAG_SQL}[Database:PlayerSamples:downsample:Error] {QUERY_FAILED}\n{e}");
+      return Err(e);
+    }
+
+    let daily = sqlx::query(
+      "INSERT INTO player_samples_daily (server_name, bucket, avg_players, peak_players, samples)
+      SELECT h.server_name, h.bucket - h.bucket % $2 AS day,
+        (SUM(h.avg_players * h.samples) / SUM(h.samples))::REAL, MAX(h.peak_players), SUM(h.samples)::INT
+      FROM player_samples_hourly h
+      WHERE h.bucket < $1 - $1 % $2
+        AND h.bucket >= COALESCE((SELECT MAX(d.bucket) FROM player_samples_daily d WHERE d.server_name = h.server_name), 0)
+      GROUP BY h.server_name, day
+      ON CONFLICT (server_name, bucket) DO UPDATE SET
+        avg_players = EXCLUDED.avg_players, peak_players = EXCLUDED.peak_players, samples = EXCLUDED.samples"
+    )
+    .bind(now)
+    .bind(DAY_SECS)
+    .execute(pool)
+    .await;
+
+    if let Err(e) = daily {
+      eprintln!("{DAG_SQL}[Database:PlayerSamples:downsample:Error] {QUERY_FAILED}\n{e}");
+      return Err(e);
+    }
+
+    Ok(())
+  }
+
+  /// Delete history that's older than the retention allows, returns how many rows were removed
+  pub async fn prune(
+    pool: &PgPool,
+    now: i64,
+    retention: PlayerRetention
+  ) -> Result<u64> {
+    let mut removed = 0;
+
+    for (table, column, days) in [
+      ("player_samples", "timestamp", retention.samples),
+      ("player_samples_hourly", "bucket", retention.hourly),
+      ("player_samples_daily", "bucket", retention.daily)
+    ] {
+      let q = sqlx::query(&format!("DELETE FROM {table} WHERE {column} < $1"))
+        .bind(now - days as i64 * DAY_SECS)
+        .execute(pool)
+        .await;
+
+      match q {
+        Ok(r) => removed += r.rows_affected(),
+        Err(e) => {
+          eprintln!("{DAG_SQL}[Database:PlayerSamples:prune:Error] {QUERY_FAILED}\n{e}");
+          return Err(e)
+        }
+      }
+    }
+
+    Ok(removed)
+  }
+
+  /// Hourly player counts for a server since the given time, oldest first.<br>
+  /// Hours that haven't been rolled up yet are aggregated from the raw samples on the fly.
+  pub async fn hourly_since(
+    pool: &PgPool,
+    server_name: &str,
+    since: i64
+  ) -> Result<Vec<HourlyPlayers>> {
+    let q = sqlx::query_as::<_, HourlyPlayers>(
+      "SELECT bucket, avg_players, peak_players, samples FROM player_samples_hourly
+      WHERE server_name = $1 AND bucket >= $2
+      UNION ALL
+      SELECT timestamp - timestamp % $3 AS bucket, AVG(players)::REAL, MAX(players), COUNT(*)::INT FROM player_samples
+      WHERE server_name = $1
+        AND timestamp >= GREATEST($2, COALESCE((SELECT MAX(bucket) + $3 FROM player_samples_hourly WHERE server_name = $1), 0))
+      GROUP BY bucket
+      ORDER BY bucket"
+    )
+    .bind(server_name)
+    .bind(since)
+    .bind(HOUR_SECS)
+    .fetch_all(pool)
+    .await;
+
+    match q {
+      Ok(rows) => Ok(rows),
+      Err(e) => {
+        eprintln!("{DAG_SQL}[Database:PlayerSamples:hourly_since:Error] {QUERY_FAILED}\n{e}");
+        Err(e)
+      }
+    }
+  }
+}
@@ -0,0 +1,231 @@
+use super::{
+  DAG_SQL,
+  QUERY_FAILED
+};
+
+use sqlx::{
+  FromRow,
+  PgPool,
+  Result,
+  Row
+};
+
+/// Time a player spent on a server between joining and leaving
+#[derive(Clone, FromRow)]
+pub struct PlayerSessions {
+  pub server_name: String,
+  pub player_name: String,
+  pub is_admin:    bool,
+  pub started_at:  i64,
+  pub ended_at:    Option<i64>,
+  /// Seconds, filled in once the session ends
+  pub duration:    Option<i32>,
+  /// Latest refresh that still saw the player on, what an open session counts up to
+  pub last_seen:   Option<i64>
+}
+
+/// Playtime summed up over a group of sessions
+#[derive(Clone, FromRow)]
+pub struct PlayerTotal {
+  /// The player on the leaderboard, or the server when looking up a single player
+  pub name:      String,
+  /// Seconds
+  pub playtime:  i64,
+  pub sessions:  i64,
+  pub last_seen: i64
+}
+
+impl PlayerSessions {
+  /// Start a session, unless the player already has one open on the server.<br>
+  /// It can't start before their previous one ended, a join backdated across a gap in the polling would count that time twice.
+  pub async fn open(
+    &self,
+    pool: &PgPool
+  ) -> Result<()> {
+    let q = sqlx::query(
+      "INSERT INTO player_sessions (server_name, player_name, is_admin, started_at, last_seen)
+      SELECT $1, $2, $3, GREATEST($4, COALESCE(MAX(ended_at), $4)), $5 FROM player_sessions WHERE server_name = $1 AND player_name = $2
+      ON CONFLICT (server_name, player_name) WHERE ended_at IS NULL DO NOTHING"
+    )
+    .bind(&self.server_name)
+    .bind(&self.player_name)
+    .bind(self.is_admin)
+    .bind(self.started_at)
+    .bind(self.last_seen)
+    .execute(pool)
+    .await;
+
+    if let Err(e) = q {
+      eprintln!("{DAG_SQL}[Database:PlayerSessions:open:Error] {QUERY_FAILED}\n{e}");
+      return Err(e);
+    }
+
+    Ok(())
+  }
+
+  /// End the player's open session, one that started at `ended_at` is left alone since that's the rejoin replacing it
+  pub async fn close(
+    pool: &PgPool,
+    server_name: &str,
+    player_name: &str,
+    ended_at: i64
+  ) -> Result<()> {
+    let q = sqlx::query(
+      "UPDATE player_sessions SET ended_at = GREATEST($3, started_at), duration = GREATEST($3 - started_at, 0)
+      WHERE server_name = $1 AND player_name = $2 AND ended_at IS NULL AND started_at < $3"
+    )
+    .bind(server_name)
+    .bind(player_name)
+    .bind(ended_at)
+    .execute(pool)
+    .await;
+
+    if let Err(e) = q {
+      eprintln!("{DAG_SQL}[Database:PlayerSessions:close:Error] {QUERY_FAILED}\n{e}");
+      return Err(e);
+    }
+
+    Ok(())
+  }
+
+  /// Mark everyone still on the server as seen at `now`
+  pub async fn touch(
+    pool: &PgPool,
+    server_name: &str,
+    now: i64
+  ) -> Result<()> {
+    let q = sqlx::query("UPDATE player_sessions SET last_seen = $2 WHERE server_name = $1 AND ended_at IS NULL")
+      .bind(server_name)
+      .bind(now)
+      .execute(pool)
+      .await;
+
+    if let Err(e) = q {
+      eprintln!("{DAG_SQL}[Database:PlayerSessions:touch:Error] {QUERY_FAILED}\n{e}");
+      return Err(e);
+    }
+
+    Ok(())
+  }
+
+  /// End the open sessions on every server that isn't in `polled` and hasn't been seen since `seen_before`,
+  /// at the last time they were seen. Returns the server of every session that was closed
+  pub async fn close_unpolled(
+    pool: &PgPool,
+    polled: &[String],
+    seen_before: i64
+  ) -> Result<Vec<String>> {
+    let q = sqlx::query(
+      "UPDATE player_sessions SET ended_at = GREATEST(COALESCE(last_seen, started_at), started_at),
+        duration = GREATEST(COALESCE(last_seen, started_at) - started_at, 0)
+      WHERE ended_at IS NULL AND server_name <> ALL($1) AND COALESCE(last_seen, started_at) < $2
+      RETURNING server_name"
+    )
+    .bind(polled)
+    .bind(seen_before)
+    .fetch_all(pool)
+    .await;
+
+    match q {
+      Ok(rows) => Ok(rows.iter().map(|r| r.get("server_name")).collect()),
+      Err(e) => {
+        eprintln!("{DAG_SQL}[Database:PlayerSessions:close_unpolled:Error] {QUERY_FAILED}\n{e}");
+        Err(e)
+      }
+    }
+  }
+
+  /// Sessions on the server that haven't ended yet
+  pub async fn get_open(
+    pool: &PgPool,
+    server_name: &str
+  ) -> Result<Vec<Self>> {
+    let q = sqlx::query_as::<_, Self>("SELECT * FROM player_sessions WHERE server_name = $1 AND ended_at IS NULL")
+      .bind(server_name)
+      .fetch_all(pool)
+      .await;
+
+    match q {
+      Ok(rows) => Ok(rows),
+      Err(e) => {
+        eprintln!("{DAG_SQL}[Database:PlayerSessions:get_open:Error] {QUERY_FAILED}\n{e}");
+        Err(e)
+      }
+    }
+  }
+
+  /// Players with the most playtime since the given time, on one server or all of them.<br>
+  /// Sessions that started earlier only count the part inside the period, ongoing ones count up to when the player was last seen.
+  pub async fn leaderboard(
+    pool: &PgPool,
+    server_name: Option<&str>,
+    since: i64,
+    limit: i64
+  ) -> Result<Vec<PlayerTotal>> {
+    let q = sqlx::query_as::<_, PlayerTotal>(
+      "SELECT player_name AS name, SUM(COALESCE(ended_at, last_seen, started_at) - GREATEST(started_at, $2))::BIGINT AS playtime,
+        COUNT(*) AS sessions, MAX(COALESCE(ended_at, last_seen, started_at)) AS last_seen
+      FROM player_sessions
+      WHERE COALESCE(ended_at, last_seen, started_at) > $2 AND ($1::VARCHAR IS NULL OR server_name = $1)
+      GROUP BY player_name ORDER BY playtime DESC LIMIT $3"
+    )
+    .bind(server_name)
+    .bind(since)
+    .bind(limit)
+    .fetch_all(pool)
+    .await;
+
+    match q {
+      Ok(rows) => Ok(rows),
+      Err(e) => {
+        eprintln!("{DAG_SQL}[Database:PlayerSessions:leaderboard:Error] {QUERY_FAILED}\n{e}");
+        Err(e)
+      }
+    }
+  }
+
+  /// A player's playtime on each server, most played first
+  pub async fn totals_for_player(
+    pool: &PgPool,
+    player_name: &str
+  ) -> Result<Vec<PlayerTotal>> {
+    let q = sqlx::query_as::<_, PlayerTotal>(
+      "SELECT server_name AS name, SUM(COALESCE(ended_at, last_seen, started_at) - started_at)::BIGINT AS playtime,
+        COUNT(*) AS sessions, MAX(COALESCE(ended_at, last_seen, started_at)) AS last_seen
+      FROM player_sessions WHERE LOWER(player_name) = LOWER($1)
+      GROUP BY server_name ORDER BY playtime DESC"
+    )
+    .bind(player_name)
+    .fetch_all(pool)
+    .await;
+
+    match q {
+      Ok(rows) => Ok(rows),
+      Err(e) => {
+        eprintln!("{DAG_SQL}[Database:PlayerSessions:totals_for_player:Error] {QUERY_FAILED}\n{e}");
+        Err(e)
+      }
+    }
+  }
+
+  /// A player's latest sessions across all servers, newest first
+  pub async fn recent_for_player(
+    pool: &PgPool,
+    player_name: &str,
+    limit: i64
+  ) -> Result<Vec<Self>> {
+    let q = sqlx::query_as::<_, Self>("SELECT * FROM player_sessions WHERE LOWER(player_name) = LOWER($1) ORDER BY started_at DESC LIMIT $2")
+      .bind(player_name)
+      .bind(limit)
+      .fetch_all(pool)
+      .await;
+
+    match q {
+      Ok(rows) => Ok(rows),
+      Err(e) => {
+        eprintln!("{DAG_SQL}[Database:PlayerSessions:recent_for_player:Error] {QUERY_FAILED}\n{e}");
+        Err(e)
+      }
+    }
+  }
+}
@@ -11,12 +11,13 @@ use sqlx::{
 
 #[derive(Clone, FromRow, PartialEq)]
 pub struct ProhibitedWords {
-  pub word: String
+  pub word:       String,
+  pub match_mode: String
 }
 
 impl ProhibitedWords {
   pub async fn get_words(pool: &PgPool) -> Result<Vec<ProhibitedWords>> {
-    let rows = match sqlx::query_as::<_, ProhibitedWords>("SELECT word FROM prohibited_words")
+    let rows = match sqlx::query_as::<_, ProhibitedWords>("SELECT word, match_mode FROM prohibited_words")
       .fetch_all(pool)
       .await
     {
@@ -32,10 +33,12 @@ impl ProhibitedWords {
 
   pub async fn add_word(
     pool: &PgPool,
-    word: &str
+    word: &str,
+    match_mode: &str
   ) -> Result<()> {
-    match sqlx::query("INSERT INTO prohibited_words (word) VALUES ($1) ON CONFLICT DO NOTHING")
+    match sqlx::query("INSERT INTO prohibited_words (word, match_mode) VALUES ($1, $2) ON CONFLICT DO NOTHING")
       .bind(word)
+      .bind(match_mode)
       .execute(pool)
       .await
     {
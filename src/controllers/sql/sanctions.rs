@@ -21,7 +21,8 @@ pub struct Sanctions {
   pub timestamp:      i64,         // Unix epoch
   pub end_time:       Option<i64>, // for bans and mutes
   pub duration:       Option<i64>, // for mutes
-  pub reason:         String       // 255 characters max
+  pub reason:         String,      // 255 characters max
+  pub voided:         bool         // pardoned automod cases
 }
 
 pub struct ReturnedCase {
@@ -52,7 +53,8 @@ impl Sanctions {
         timestamp:      r.get("timestamp"),
         end_time:       r.try_get("end_time").ok(),
         duration:       r.try_get("duration").ok(),
-        reason:         r.get("reason")
+        reason:         r.get("reason"),
+        voided:         r.try_get("voided").unwrap_or(false)
       }))
     } else {
       Ok(None)
@@ -101,6 +103,25 @@ impl Sanctions {
     }
   }
 
+  /// Mark the case as voided, returns false if it was already voided or doesn't exist
+  pub async fn void(
+    pool: &PgPool,
+    case_id: i32
+  ) -> Result<bool> {
+    let q = sqlx::query("UPDATE sanctions SET voided = TRUE WHERE case_id = $1 AND NOT voided")
+      .bind(case_id)
+      .execute(pool)
+      .await;
+
+    match q {
+      Ok(r) => Ok(r.rows_affected() > 0),
+      Err(e) => {
+        eprintln!("{DAG_SQL}[Database:Sanctions:void:Error] {QUERY_FAILED}\n{e}");
+        Err(e)
+      }
+    }
+  }
+
   pub async fn get_cases(pool: &PgPool) -> Result<Vec<ReturnedCase>> {
     let q = sqlx::query("SELECT case_id, case_type, member_id, member_name FROM sanctions")
       .fetch_all(pool)
@@ -0,0 +1,72 @@
+use super::{
+  DAG_SQL,
+  QUERY_FAILED
+};
+
+use sqlx::{
+  FromRow,
+  PgPool,
+  Result
+};
+
+#[derive(Clone, FromRow)]
+pub struct ScamImages {
+  pub hash:     i64,
+  pub label:    String,
+  pub added_by: i64,
+  pub added_at: i64
+}
+
+impl ScamImages {
+  pub async fn get_images(pool: &PgPool) -> Result<Vec<ScamImages>> {
+    let rows = match sqlx::query_as::<_, ScamImages>("SELECT * FROM scam_images ORDER BY added_at")
+      .fetch_all(pool)
+      .await
+    {
+      Ok(r) => r,
+      Err(e) => {
+        eprintln!("{DAG_SQL}[Database:ScamImages:get_images:Error] {QUERY_FAILED}\n{e}");
+        return Err(e)
+      }
+    };
+
+    Ok(rows)
+  }
+
+  pub async fn add(
+    &self,
+    pool: &PgPool
+  ) -> Result<bool> {
+    let q = sqlx::query("INSERT INTO scam_images (hash, label, added_by, added_at) VALUES ($1, $2, $3, $4) ON CONFLICT DO NOTHING")
+      .bind(self.hash)
+      .bind(&self.label)
+      .bind(self.added_by)
+      .bind(self.added_at)
+      .execute(pool)
+      .await;
+
+    match q {
+      Ok(r) => Ok(r.rows_affected() > 0),
+      Err(e) => {
+        eprintln!("{DAG_SQL}[Database:ScamImages:add:Error] {QUERY_FAILED}\n{e}");
+        Err(e)
+      }
+    }
+  }
+
+  /// Remove every image with the given label, returns how many were removed
+  pub async fn remove_label(
+    pool: &PgPool,
+    label: &str
+  ) -> Result<u64> {
+    let q = sqlx::query("DELETE FROM scam_images WHERE label = $1").bind(label).execute(pool).await;
+
+    match q {
+      Ok(r) => Ok(r.rows_affected()),
+      Err(e) => {
+        eprintln!("{DAG_SQL}[Database:ScamImages:remove_label:Error] {QUERY_FAILED}\n{e}");
+        Err(e)
+      }
+    }
+  }
+}
@@ -15,8 +15,19 @@ pub struct Settings {
   pub logs_ignored_channels: Vec<i64>
 }
 
+/// How many days of player history is kept at each resolution
+#[derive(Debug, Clone, Copy, FromRow)]
+pub struct PlayerRetention {
+  #[sqlx(rename = "sample_retention_days")]
+  pub samples: i32,
+  #[sqlx(rename = "hourly_retention_days")]
+  pub hourly:  i32,
+  #[sqlx(rename = "daily_retention_days")]
+  pub daily:   i32
+}
+
 impl Settings {
-  pub async fn get_logs_ignored_channels(pool: &PgPool) -> Result<Vec<i64>> {
+  async fn ensure_row(pool: &PgPool) -> Result<()> {
     let row_exists = sqlx::query("SELECT EXISTS(SELECT 1 FROM settings WHERE id = 1)")
       .fetch_one(pool)
       .await?
@@ -28,6 +39,12 @@ impl Settings {
         .await?;
     }
 
+    Ok(())
+  }
+
+  pub async fn get_logs_ignored_channels(pool: &PgPool) -> Result<Vec<i64>> {
+    Self::ensure_row(pool).await?;
+
     let q = sqlx::query_as::<_, Self>("SELECT logs_ignored_channels FROM settings WHERE id = 1")
       .fetch_one(pool)
       .await;
@@ -56,4 +73,41 @@ impl Settings {
 
     Ok(())
   }
+
+  pub async fn get_player_retention(pool: &PgPool) -> Result<PlayerRetention> {
+    Self::ensure_row(pool).await?;
+
+    let q =
+      sqlx::query_as::<_, PlayerRetention>("SELECT sample_retention_days, hourly_retention_days, daily_retention_days FROM settings WHERE id = 1")
+        .fetch_one(pool)
+        .await;
+
+    if let Err(e) = q {
+      eprintln!("{DAG_SQL}[Database:Settings:get_player_retention:Error] {QUERY_FAILED}\n{e}");
+      return Err(e);
+    };
+
+    Ok(q.unwrap())
+  }
+
+  pub async fn update_player_retention(
+    pool: &PgPool,
+    retention: PlayerRetention
+  ) -> Result<()> {
+    Self::ensure_row(pool).await?;
+
+    let q = sqlx::query("UPDATE settings SET sample_retention_days = $1, hourly_retention_days = $2, daily_retention_days = $3 WHERE id = 1")
+      .bind(retention.samples)
+      .bind(retention.hourly)
+      .bind(retention.daily)
+      .execute(pool)
+      .await;
+
+    if let Err(e) = q {
+      eprintln!("{DAG_SQL}[Database:Settings:update_player_retention:Error] {QUERY_FAILED}\n{e}");
+      return Err(e);
+    };
+
+    Ok(())
+  }
 }
@@ -0,0 +1,65 @@
+use super::{
+  DAG_SQL,
+  QUERY_FAILED
+};
+
+use sqlx::{
+  FromRow,
+  PgPool,
+  Result
+};
+
+#[derive(Clone, FromRow, PartialEq)]
+pub struct WordAllowlist {
+  pub word: String
+}
+
+impl WordAllowlist {
+  pub async fn get_words(pool: &PgPool) -> Result<Vec<WordAllowlist>> {
+    let rows = match sqlx::query_as::<_, WordAllowlist>("SELECT word FROM word_allowlist")
+      .fetch_all(pool)
+      .await
+    {
+      Ok(r) => r,
+      Err(e) => {
+        eprintln!("{DAG_SQL}[Database:WordAllowlist:get_words:Error] {QUERY_FAILED}\n{e}");
+        return Err(e)
+      }
+    };
+
+    Ok(rows)
+  }
+
+  pub async fn add_word(
+    pool: &PgPool,
+    word: &str
+  ) -> Result<bool> {
+    let q = sqlx::query("INSERT INTO word_allowlist (word) VALUES ($1) ON CONFLICT DO NOTHING")
+      .bind(word)
+      .execute(pool)
+      .await;
+
+    match q {
+      Ok(r) => Ok(r.rows_affected() > 0),
+      Err(e) => {
+        eprintln!("{DAG_SQL}[Database:WordAllowlist:add_word:Error] {QUERY_FAILED}\n{e}");
+        Err(e)
+      }
+    }
+  }
+
+  pub async fn remove_word(
+    pool: &PgPool,
+    word: &str
+  ) -> Result<bool> {
+    let q = sqlx::query("DELETE FROM word_allowlist WHERE word = $1").bind(word).execute(pool).await;
+
+    match q {
+      Ok(r) => Ok(r.rows_affected() > 0),
+      Err(e) => {
+        eprintln!("{DAG_SQL}[Database:WordAllowlist:remove_word:Error] {QUERY_FAILED}\n{e}");
+        Err(e)
+      }
+    }
+  }
+}
@@ -1,4 +1,6 @@
 mod audit_log;
+#[cfg(feature = "automod")]
+mod interaction;
 pub mod invite;
 mod member;
 mod message;
@@ -35,11 +37,14 @@ impl EventHandler for DiscordEvents {
         ..
       } => message::on_message_delete(ctx, channel_id, deleted_message_id).await.unwrap(),
       FullEvent::GuildMemberAddition { new_member, .. } => member::on_guild_member_addition(ctx, new_member).await.unwrap(),
+      FullEvent::GuildMemberUpdate { new, .. } => member::on_guild_member_update(ctx, new).await.unwrap(),
       FullEvent::GuildMemberRemoval {
         member_data_if_available,
         user,
         ..
       } => member::on_guild_member_removal(ctx, member_data_if_available, user).await.unwrap(),
+      #[cfg(feature = "automod")]
+      FullEvent::InteractionCreate { interaction, .. } => interaction::on_interaction_create(ctx, interaction).await.unwrap(),
       FullEvent::GuildAuditLogEntryCreate { entry, guild_id, .. } => audit_log::on_audit_log_entry_create(ctx, entry, guild_id).await.unwrap(),
       _ => ()
     }
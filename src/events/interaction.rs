@@ -0,0 +1,174 @@
+use crate::{
+  BotData,
+  BotError,
+  commands::ActionType,
+  controllers::{
+    automod::{
+      AutomodPolicyType,
+      PARDON_BUTTON
+    },
+    sql::{
+      AutomodFeedback,
+      Sanctions
+    }
+  },
+  internals::config::BINARY_PROPERTIES
+};
+
+use {
+  poise::serenity_prelude::{
+    ComponentInteraction,
+    Context,
+    CreateEmbed,
+    CreateInteractionResponse,
+    CreateInteractionResponseMessage,
+    Error,
+    HttpError,
+    Interaction,
+    JsonErrorCode,
+    Mentionable,
+    UserId
+  },
+  std::{
+    str::FromStr,
+    time::{
+      SystemTime,
+      UNIX_EPOCH
+    }
+  }
+};
+
+pub async fn on_interaction_create(
+  ctx: &Context,
+  interaction: &Interaction
+) -> Result<(), BotError> {
+  let Some(component) = interaction.as_message_component() else {
+    return Ok(());
+  };
+
+  let mut parts = component.data.custom_id.split(':');
+  if let (Some(PARDON_BUTTON), Some(case_id), Some(policy_type)) = (parts.next(), parts.next(), parts.next()) {
+    match (case_id.parse::<i32>(), AutomodPolicyType::from_str(policy_type)) {
+      (Ok(case_id), Ok(policy_type)) => pardon_case(ctx, component, case_id, policy_type).await?,
+      _ => eprintln!("Interaction[Pardon:Error] Malformed button ID: {}", component.data.custom_id)
+    }
+  }
+
+  Ok(())
+}
+
+async fn reply_ephemeral(
+  ctx: &Context,
+  component: &ComponentInteraction,
+  content: &str
+) -> Result<(), BotError> {
+  component
+    .create_response(
+      &ctx.http,
+      CreateInteractionResponse::Message(CreateInteractionResponseMessage::new().content(content).ephemeral(true))
+    )
+    .await?;
+
+  Ok(())
+}
+
+/// Whether Discord says there's no ban or member left, which means someone already undid it by hand.<br>
+/// A muted member who has since left also comes back as Unknown Member, and there's nothing left to lift then either.
+fn already_lifted(error: &Error) -> bool {
+  match error {
+    Error::Http(HttpError::UnsuccessfulRequest(response)) => {
+      matches!(response.error.code, JsonErrorCode::UnknownBan | JsonErrorCode::UnknownMember)
+    },
+    _ => false
+  }
+}
+
+/// Undo an automod sanction that staff deemed a false positive
+async fn pardon_case(
+  ctx: &Context,
+  component: &ComponentInteraction,
+  case_id: i32,
+  policy_type: AutomodPolicyType
+) -> Result<(), BotError> {
+  let permissions = component.member.as_ref().and_then(|m| m.permissions).unwrap_or_default();
+  if !permissions.moderate_members() {
+    return reply_ephemeral(ctx, component, "You need the **Moderate Members** permission to pardon automod cases").await;
+  }
+
+  let data = ctx.data::<BotData>();
+  let case = match Sanctions::load_data(&data.postgres, case_id).await? {
+    Some(c) => c,
+    None => return reply_ephemeral(ctx, component, &format!("Case #{case_id} isn't in the database")).await
+  };
+
+  if case.voided {
+    return reply_ephemeral(ctx, component, &format!("Case #{case_id} has already been pardoned")).await;
+  }
+
+  let guild_id = component.guild_id.expect("Expected interaction to be in guild");
+  let user_id = UserId::new(case.member_id.parse::<u64>()?);
+
+  let lifted = match ActionType::from_str(&case.case_type) {
+    Ok(ActionType::Mute) => async { guild_id.member(&ctx.http, user_id).await?.enable_communication(&ctx.http).await }.await,
+    Ok(ActionType::Ban) => {
+      if !permissions.ban_members() {
+        return reply_ephemeral(ctx, component, "You need the **Ban Members** permission to pardon an automod ban").await;
+      }
+      guild_id.unban(&ctx.http, user_id, Some("Pardoned automod case")).await
+    },
+    _ => Ok(())
+  };
+
+  // Keep the case standing if the sanction couldn't be lifted, so the button can be tried again
+  match lifted {
+    Err(e) if !already_lifted(&e) => {
+      eprintln!("Interaction[Pardon:Error] Couldn't lift case #{case_id}: {e}");
+      return reply_ephemeral(
+        ctx,
+        component,
+        &format!("Couldn't lift the sanction on case #{case_id}, try again in a bit")
+      )
+      .await;
+    },
+    _ => ()
+  }
+
+  if !Sanctions::void(&data.postgres, case_id).await? {
+    return reply_ephemeral(ctx, component, &format!("Case #{case_id} has already been pardoned")).await;
+  }
+
+  data.automod.pardon_warnings(user_id, &policy_type).await?;
+
+  let feedback = AutomodFeedback {
+    case_id,
+    policy_type: policy_type.as_str().to_string(),
+    user_id: user_id.get() as i64,
+    moderator_id: component.user.id.get() as i64,
+    timestamp: SystemTime::now()
+      .duration_since(UNIX_EPOCH)
+      .expect("System time is lagging behind or is in the future")
+      .as_secs() as i64
+  };
+  feedback.record(&data.postgres).await?;
+
+  let embed = component
+    .message
+    .embeds
+    .first()
+    .map_or_else(CreateEmbed::new, |e| CreateEmbed::from(e.clone()))
+    .color(BINARY_PROPERTIES.embed_colors.green)
+    .field(
+      "Pardoned by",
+      format!("{}\n{}\n`{}`", component.user.name, component.user.mention(), component.user.id),
+      false
+    );
+
+  component
+    .create_response(
+      &ctx.http,
+      CreateInteractionResponse::UpdateMessage(CreateInteractionResponseMessage::new().embed(embed).components(vec![]))
+    )
+    .await?;
+
+  Ok(())
+}
@@ -4,10 +4,14 @@ use crate::{
   internals::{
     config::BINARY_PROPERTIES,
     invite_data::InviteData,
+    nickname,
     utils::format_timestamp
   }
 };
 
+#[cfg(not(feature = "automod"))]
+use crate::internals::word_filter::WordFilter;
+
 use {
   poise::serenity_prelude::{
     Context,
@@ -35,6 +39,45 @@ pub(super) struct CachedMember {
   pub user:  User
 }
 
+async fn sanitise_nickname(
+  ctx: &Context,
+  member: &Member
+) -> Result<(), BotError> {
+  #[cfg(feature = "automod")]
+  let prohibited = ctx.data::<BotData>().automod.word_filter();
+
+  // A database blip shouldn't take the rest of the member event down with it
+  #[cfg(not(feature = "automod"))]
+  let prohibited = match WordFilter::load(&ctx.data::<BotData>().postgres).await {
+    Ok(p) => p,
+    Err(e) => {
+      eprintln!("Nickname[Error] Failed to load the prohibited words for {}: {e}", member.user.name);
+      return Ok(());
+    }
+  };
+
+  if let Err(e) = nickname::enforce(&ctx.http, member, &prohibited, true).await {
+    eprintln!("Nickname[Error] Failed to sanitise {}'s nickname: {e}", member.user.name);
+  }
+
+  Ok(())
+}
+
+/// Let automod screen the member's names first, it takes over the nickname if they're in violation
+async fn screen_member(
+  ctx: &Context,
+  member: &Member
+) -> Result<(), BotError> {
+  #[cfg(feature = "automod")]
+  match ctx.data::<BotData>().automod.screen_member(ctx, member).await {
+    Ok(true) => return Ok(()),
+    Ok(false) => (),
+    Err(e) => eprintln!("Automod[Error] Failed to screen {}'s profile: {e}", member.user.name)
+  }
+
+  sanitise_nickname(ctx, member).await
+}
+
 pub async fn on_guild_member_addition(
   ctx: &Context,
   new_member: &Member
@@ -62,6 +105,8 @@ pub async fn on_guild_member_addition(
     println!("GuildMemberAddition[Debug] WS event received, preparing to fire welcome message");
     println!("GuildMemberAddition[Debug] Gateway sent member data for {}", new_member.user.tag());
 
+    screen_member(ctx, new_member).await?;
+
     let cached_guild = match new_member.guild_id.to_guild_cached(&ctx.cache) {
       Some(g) => g.clone(),
       None => return Ok(())
@@ -170,6 +215,18 @@ pub async fn on_guild_member_addition(
   Ok(())
 }
 
+pub async fn on_guild_member_update(
+  ctx: &Context,
+  new: &Option<Member>
+) -> Result<(), BotError> {
+  let member = match new {
+    Some(m) if m.guild_id == GuildId::new(BINARY_PROPERTIES.guild_id) => m,
+    _ => return Ok(())
+  };
+
+  screen_member(ctx, member).await
+}
+
 pub async fn on_guild_member_removal(
   ctx: &Context,
   member_data_if_available: &Option<Member>,
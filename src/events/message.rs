@@ -226,30 +226,40 @@ pub async fn on_message_update(
   let redis = &ctx.data::<BotData>().redis;
   let rkey = REDIS_MSG_KEY.replace("{{ message_id }}", &event.message.id.to_string());
 
-  let mut get_cached_msg: CachedMessage = match redis.get(&rkey).await {
-    Ok(m) => {
-      let msg = match m {
-        Some(msg) => msg,
-        None => {
-          #[cfg(not(feature = "production"))]
-          eprintln!("MessageUpdate[Error] Message not found in cache");
-          return Ok(());
-        }
-      };
-      match serde_json::from_str(&msg) {
-        Ok(c) => c,
-        Err(e) => {
-          eprintln!("MessageUpdate[Deserialization:Error] {e}");
-          return Ok(());
-        }
+  let cached_msg: Option<CachedMessage> = match redis.get(&rkey).await {
+    Ok(Some(msg)) => match serde_json::from_str(&msg) {
+      Ok(c) => Some(c),
+      Err(e) => {
+        eprintln!("MessageUpdate[Deserialization:Error] {e}");
+        None
       }
     },
+    Ok(None) => {
+      #[cfg(not(feature = "production"))]
+      eprintln!("MessageUpdate[Error] Message not found in cache");
+      None
+    },
     Err(e) => {
       eprintln!("MessageUpdate[Error] {e}");
-      return Ok(());
+      None
     }
   };
 
+  // Embeds getting resolved also fire this event, only actual edits have the timestamp
+  #[cfg(feature = "automod")]
+  if event.message.edited_timestamp.is_some() && event.message.guild_id == Some(GuildId::new(BINARY_PROPERTIES.guild_id)) {
+    let previous = cached_msg.as_ref().map(|c| c.attachments.iter().map(|a| a.id).collect::<Vec<_>>());
+    ctx
+      .data::<BotData>()
+      .automod
+      .process_edit(ctx, &event.message, previous.as_deref())
+      .await?;
+  }
+
+  let Some(mut get_cached_msg) = cached_msg else {
+    return Ok(());
+  };
+
   match get_cached_msg.content.as_str() {
     "" => get_cached_msg.content = ATTACHMENT_TXT.clone(),
     content => match event.message.content.as_str() {
@@ -333,11 +343,7 @@ pub async fn on_message(
   }
 
   #[cfg(feature = "automod")]
-  {
-    use crate::controllers::automod::Automoderator;
-    let automod = Automoderator::new(&ctx.data::<BotData>().postgres, ctx.data::<BotData>().redis.clone()).await?;
-    automod.process_message(ctx, new_message).await?;
-  }
+  ctx.data::<BotData>().automod.process_message(ctx, new_message).await?;
 
   let cached_message = CachedMessage {
     content:     new_message.content.clone(),
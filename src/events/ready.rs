@@ -4,7 +4,6 @@ use crate::{
   GIT_COMMIT_HASH,
   internals::{
     config::BINARY_PROPERTIES,
-    tasks,
     utils::BOT_VERSION
   }
 };
@@ -27,12 +26,9 @@ use {
   },
   std::{
     fs,
-    sync::{
-      Arc,
-      atomic::{
-        AtomicBool,
-        Ordering
-      }
+    sync::atomic::{
+      AtomicBool,
+      Ordering
     },
     thread::current
   }
@@ -113,9 +109,5 @@ pub async fn on_ready(
 
   ctx.set_activity(Some(ActivityData::streaming(activity.name.clone(), activity.url.clone()).unwrap()));
 
-  // I am very aware that this is deprecated but in order to migrate Monica to a
-  // new scheduler involves rewriting the majority of the main loop in that file
-  tasks::run_task(Arc::new(ctx.clone()), tasks::monica, "Monica").await;
-
   Ok(())
 }
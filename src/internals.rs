@@ -2,8 +2,10 @@ pub mod ansi;
 pub mod canvas;
 pub mod config;
 pub mod invite_data;
+pub mod nickname;
 pub mod scheduler;
 pub mod seasonal;
 pub mod tasks;
 pub mod tsclient;
 pub mod utils;
+pub mod word_filter;
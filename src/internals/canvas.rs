@@ -31,6 +31,8 @@ const INTERPOLATION_STEPS: usize = 100;
 pub struct Canvas {
   canvas:         ImageBuffer<Rgba<u8>, Vec<u8>>,
   palette:        Palette,
+  /// Value at the top of the graph
+  ceiling:        f64,
   line_thickness: i32,
   dot_thickness:  i32
 }
@@ -100,11 +102,21 @@ impl Canvas {
     Self {
       canvas,
       palette,
+      ceiling: 16.0, // server slots
       line_thickness,
       dot_thickness
     }
   }
 
+  /// Change the value at the top of the graph, defaults to the server's 16 slots
+  pub fn ceiling(
+    mut self,
+    ceiling: f64
+  ) -> Self {
+    self.ceiling = ceiling;
+    self
+  }
+
   fn calculate_score(interval: f64) -> f64 {
     let interval_str = interval.to_string();
     let zero_count = interval_str.matches('0').count() as f64;
@@ -161,7 +173,7 @@ impl Canvas {
       }
     }
 
-    let top = 16.0;
+    let top = self.ceiling;
     let text_size = 40.0;
     let origin = (15, 65);
     let size = (1300, 630);
@@ -222,7 +234,7 @@ impl Canvas {
       let y = ((1.0 - (current_value / top)) * size.1 as f64 + y_offset) as i32;
 
       let color = {
-        const HIGH_THRESHOLD: f64 = 11.0 / 16.0; // paint red if 11+ players
+        const HIGH_THRESHOLD: f64 = 11.0 / 16.0; // paint red if 11+ players on a 16 slot server
         const LOW_THRESHOLD: f64 = 6.0 / 16.0; // otherwise paint yellow if 6+
 
         let relative_position = current_value / top;
@@ -0,0 +1,127 @@
+use crate::{
+  BotError,
+  internals::{
+    config::BINARY_PROPERTIES,
+    word_filter::{
+      WordFilter,
+      is_combining,
+      is_invisible
+    }
+  }
+};
+
+use {
+  poise::serenity_prelude::{
+    CreateEmbed,
+    CreateEmbedAuthor,
+    CreateMessage,
+    EditMember,
+    GenericChannelId,
+    Http,
+    Member,
+    Timestamp
+  },
+  unicode_normalization::UnicodeNormalization
+};
+
+/// Used when neither the display name nor the username is salvageable
+pub const FALLBACK_NAME: &str = "Moderated Nickname";
+/// Discord's upper limit for nicknames
+const MAX_NICK_LEN: usize = 32;
+/// A name needs at least this many readable characters to be kept
+const MIN_READABLE: usize = 2;
+
+/// Characters that push a name to the top of the member list
+fn is_hoisting(c: char) -> bool { !c.is_alphanumeric() }
+
+/// Fold fancy fonts, strip zalgo and invisible characters, then strip leading hoisting characters.<br>
+/// Returns an empty string if nothing readable is left.
+fn clean(name: &str) -> String {
+  let folded: String = name.nfkc().filter(|c| !is_combining(*c) && !is_invisible(*c)).collect();
+  let collapsed = folded.split_whitespace().collect::<Vec<_>>().join(" ");
+  let trimmed = collapsed.trim_start_matches(is_hoisting).trim_end();
+
+  if trimmed.chars().filter(|c| c.is_alphanumeric()).count() < MIN_READABLE {
+    return String::new();
+  }
+
+  trimmed.chars().take(MAX_NICK_LEN).collect()
+}
+
+/// Work out what the member's name should be.<br>
+/// Returns `None` if the current name is already fine.
+pub fn sanitise(
+  display_name: &str,
+  username: &str,
+  prohibited: &WordFilter
+) -> Option<String> {
+  let cleaned = clean(display_name);
+
+  let replacement = if !cleaned.is_empty() && !prohibited.is_match(&cleaned) {
+    cleaned
+  } else {
+    let fallback = clean(username);
+    if !fallback.is_empty() && !prohibited.is_match(&fallback) {
+      fallback
+    } else {
+      FALLBACK_NAME.to_string()
+    }
+  };
+
+  if replacement == display_name { None } else { Some(replacement) }
+}
+
+/// Sanitise the member's nickname and log the change to the bot log.<br>
+/// Returns the old and new name if the member got renamed.
+pub async fn enforce(
+  http: &Http,
+  member: &Member,
+  prohibited: &WordFilter,
+  log_change: bool
+) -> Result<Option<(String, String)>, BotError> {
+  if member.user.bot() {
+    return Ok(None);
+  }
+
+  let old_name = member.display_name().to_string();
+  let new_name = match sanitise(&old_name, &member.user.name, prohibited) {
+    Some(n) => n,
+    None => return Ok(None)
+  };
+
+  // If the cleaned name ends up being the username, just remove the nickname instead
+  let nick = if new_name == member.user.name.as_str() && member.user.global_name.is_none() {
+    String::new()
+  } else {
+    new_name.clone()
+  };
+
+  member
+    .guild_id
+    .edit_member(
+      http,
+      member.user.id,
+      EditMember::new().nickname(nick).audit_log_reason("Nickname sanitisation")
+    )
+    .await?;
+
+  println!("Nickname[Info] Renamed {} from \"{old_name}\" to \"{new_name}\"", member.user.name);
+
+  if log_change {
+    GenericChannelId::new(BINARY_PROPERTIES.bot_log)
+      .send_message(
+        http,
+        CreateMessage::new().embed(
+          CreateEmbed::new()
+            .color(BINARY_PROPERTIES.embed_colors.yellow)
+            .author(CreateEmbedAuthor::new(format!("{} ({})", member.user.name, member.user.id)).icon_url(member.user.face()))
+            .title("Nickname sanitised")
+            .fields(vec![("Old", format!("`{old_name}`"), false), ("New", format!("`{new_name}`"), false)])
+            .timestamp(Timestamp::now())
+        )
+      )
+      .await?;
+  }
+
+  Ok(Some((old_name, new_name)))
+}
@@ -2,7 +2,18 @@ use {
   super::{
     super::{
       config::BINARY_PROPERTIES,
-      utils::discord_token
+      scheduler::TaskScheduler
+    },
+    notifications::{
+      ServerState,
+      notify_subscribers
+    },
+    sessions::{
+      Roster,
+      RosterChanges,
+      diff_rosters,
+      roster_from_players,
+      roster_from_sessions
     },
     task_err,
     task_info
@@ -15,6 +26,8 @@ use {
       cache::RedisController,
       sql::{
         MpServers,
+        PlayerSamples,
+        PlayerSessions,
         Webhooks
       }
     }
@@ -23,6 +36,11 @@ use {
 
 use {
   dag_grpc::FetchRequest,
+  dashmap::{
+    DashMap,
+    DashSet
+  },
+  futures::future::join_all,
   image::Rgba,
   lazy_static::lazy_static,
   num_format::{
@@ -31,7 +49,6 @@ use {
   },
   poise::serenity_prelude::{
     AutocompleteChoice,
-    Context,
     CreateAutocompleteResponse,
     CreateEmbed,
     CreateEmbedAuthor,
@@ -41,12 +58,12 @@ use {
     EmbedField,
     ExecuteWebhook,
     GenericChannelId,
-    Http,
     MessageId,
     ThreadId,
     Timestamp,
     Webhook,
-    WebhookId
+    WebhookId,
+    async_trait
   },
   regex::Regex,
   serde::{
@@ -59,17 +76,14 @@ use {
     sync::{
       Arc,
       atomic::{
-        AtomicI32,
-        Ordering::{
-          Acquire,
-          Release
-        }
+        AtomicBool,
+        Ordering::SeqCst
       }
     }
   },
   tokio::time::{
     Duration,
-    interval
+    timeout
   }
 };
 
@@ -77,6 +91,10 @@ pub static TASK_NAME: &str = "Monica";
 static NO_SERVERS_TEXT: &str = "No servers are available at this time";
 static REFRESH_TEXT: &str = "Refreshes every {{ refresh.timer }} seconds";
 static REFRESH_TIMER_SECS: u64 = 40;
+/// Refreshes a server can miss before its open sessions are ended, so one slow response doesn't split everyone's session
+const UNPOLLED_GRACE_REFRESHES: u64 = 3;
+/// How long a server gets to respond before it's left out of the refresh
+const FETCH_TIMEOUT: Duration = Duration::from_secs(15);
 pub static EMPTY_PLAYER_LIST_TEXT: &str = "*Nobody is playing*";
 pub static SERVER_SEARCH_FILTERS: &str = "https://discord.com/channels/468835415093411861/468835769092669461/1331780599228399636";
 
@@ -89,7 +107,6 @@ const UNKNOWN_TIME: &str = "`[UNKNOWN_TIME]`";
 const CONSOLE_SLOT_LIMIT: i32 = 3500;
 
 lazy_static! {
-  static ref PREVIOUS_DAY_TIME: AtomicI32 = AtomicI32::new(0);
   static ref SETTINGS_TXT_MAP: HashMap<TxtMapKey, HashMap<&'static str, &'static str>> = {
     [
       (TxtMapKey::GenericBools, [("false", "Off"), ("true", "On")].iter().cloned().collect()),
@@ -368,26 +385,58 @@ async fn cache_servers(
   Ok(())
 }
 
-pub async fn monica(ctx: Arc<Context>) -> Result<(), BotError> {
-  let mut interval = interval(Duration::from_secs(REFRESH_TIMER_SECS));
-  let bot_data = ctx.data::<BotData>();
-  let redis = bot_data.redis.clone();
-  let postgres = bot_data.postgres.clone();
-  let mut showing_no_servers = false;
+/// What a server contributed to the refresh
+#[derive(Default)]
+struct ServerRefresh {
+  /// Nothing is shown for the server when it's `None`
+  embed:        Option<CreateEmbed<'static>>,
+  peak_changed: bool,
+  /// Whether the players were read and their sessions brought up to date
+  polled:       bool
+}
+
+impl From<CreateEmbed<'static>> for ServerRefresh {
+  fn from(embed: CreateEmbed<'static>) -> Self {
+    Self {
+      embed:        Some(embed),
+      peak_changed: false,
+      polled:       false
+    }
+  }
+}
+
+/// Keeps the server list in the MP info channel up to date with what Monica reports
+#[derive(Default)]
+pub struct Monica {
+  /// In-game time each server was at on the previous refresh, keyed by server name
+  day_times:          DashMap<String, i32>,
+  /// Players seen on each server on the previous refresh, keyed by server name
+  rosters:            DashMap<String, Roster>,
+  /// Whether each server was up and how full it was on the previous refresh, keyed by server name
+  server_states:      DashMap<String, ServerState>,
+  /// Servers whose sessions were ended while they couldn't be polled, their roster is kept for the join feed only
+  sessions_ended:     DashSet<String>,
+  showing_no_servers: AtomicBool
+}
+
+#[async_trait]
+impl TaskScheduler for Monica {
+  fn name(&self) -> &'static str { TASK_NAME }
 
-  let mp_info = GenericChannelId::new(BINARY_PROPERTIES.mp_channels.info);
-  let mp_info_msg = MessageId::new(BINARY_PROPERTIES.mp_channels.info_msg);
+  fn interval_secs(&self) -> u64 { REFRESH_TIMER_SECS }
 
-  loop {
-    interval.tick().await;
-    let servers = MpServers::get_servers(&postgres).await?;
-    let mut embeds: Vec<CreateEmbed<'_>> = Vec::with_capacity(servers.len());
+  async fn main_loop(
+    &self,
+    bot_data: Arc<BotData>
+  ) -> Result<(), BotError> {
+    let redis = &bot_data.redis;
+    let servers = MpServers::get_servers(&bot_data.postgres).await?;
 
     let servers_in_cache: String = match redis.get(TASK_NAME).await {
       Ok(v) => v.unwrap_or_default(),
       Err(e) => {
         task_err(TASK_NAME, &format!("[monica] Redis failed to get servers: {e}"));
-        continue;
+        return Ok(());
       }
     };
     let cached_servers: Vec<MpServers>;
@@ -396,277 +445,440 @@ pub async fn monica(ctx: Arc<Context>) -> Result<(), BotError> {
       #[cfg(not(feature = "production"))]
       task_info(TASK_NAME, "Redis cache must've expired, pulling fresh data from database...");
       cached_servers = servers.clone();
-      cache_servers(&redis, servers.clone()).await?;
+      cache_servers(redis, servers.clone()).await?;
     } else {
       #[cfg(not(feature = "production"))]
       task_info(TASK_NAME, "Cache hit, using cached data...");
       cached_servers = serde_json::from_str(&servers_in_cache)?;
     }
 
-    for server in &cached_servers {
-      if !server.is_active {
-        continue;
+    // One slow server shouldn't hold up the rest, they're all fetched at once and shown in their usual order
+    let active: Vec<&MpServers> = cached_servers.iter().filter(|s| s.is_active).collect();
+    let results = join_all(active.iter().map(|server| self.refresh_server(&bot_data, server))).await;
+
+    let mut embeds: Vec<CreateEmbed<'_>> = Vec::with_capacity(active.len());
+    let mut peak_changed = false;
+    let mut polled: Vec<String> = Vec::with_capacity(active.len());
+    for (server, result) in active.iter().zip(results) {
+      match result {
+        Ok(refresh) => {
+          peak_changed |= refresh.peak_changed;
+          embeds.extend(refresh.embed);
+          if refresh.polled {
+            polled.push(server.name.clone());
+          }
+        },
+        Err(e) => task_err(TASK_NAME, &format!("[monica:{}] Failed to refresh the server: {e}", server.name))
       }
+    }
 
-      let data = match bot_data
-        .grpc
-        .clone()
-        .fetch_data(FetchRequest {
-          server_name: server.name.clone(),
-          server_ip:   server.ip.clone(),
-          server_code: server.code.clone(),
-          is_active:   server.is_active,
-          fetch_type:  "both".to_string()
-        })
-        .await
-      {
-        Ok(d) => {
-          let response_data = d.into_inner().data;
+    self.end_unpolled_sessions(&bot_data, &active, &polled).await;
 
-          if response_data.is_empty() {
-            embeds.push(
-              CreateEmbed::new()
-                .color(EmbedPalette::new().red)
-                .title(server.name.to_string())
-                .description(":no_entry_sign: **Monica passed empty data!**")
-                .timestamp(Timestamp::now())
-            );
-            continue;
-          }
+    if peak_changed {
+      cache_servers(redis, servers).await?;
+    }
 
-          response_data
-        },
-        Err(e) => {
-          if e.message().contains("request timed out: deadline has elapsed") {
-            continue;
-          }
+    let mp_info = GenericChannelId::new(BINARY_PROPERTIES.mp_channels.info);
+    let mp_info_msg = MessageId::new(BINARY_PROPERTIES.mp_channels.info_msg);
 
-          eprintln!("gRPC[Error] Monica reported an error: {e}");
-          embeds.push(
-            CreateEmbed::new()
-              .color(EmbedPalette::new().red)
-              .title(server.name.to_string())
-              .description(":no_entry_sign: **Monica is currently unavailable!**")
-              .timestamp(Timestamp::now())
-          );
-          continue;
+    if embeds.is_empty() {
+      if !self.showing_no_servers.swap(true, SeqCst) {
+        task_info(TASK_NAME, "[monica] No embeds to update message with");
+        if let Err(y) = mp_info
+          .edit_message(&bot_data.http, mp_info_msg, EditMessage::new().content(NO_SERVERS_TEXT).embeds(vec![]))
+          .await
+        {
+          task_err(TASK_NAME, &format!("[monica] Error editing message: {y}"));
         }
-      };
+      }
+      return Ok(());
+    }
 
-      let json_value: Value = match serde_json::from_str(&data) {
-        Ok(val) => val,
-        Err(e) => {
-          task_err(TASK_NAME, &format!("[monica:debug_dump] Invalid JSON structure: {e}"));
-          embeds.push(
-            CreateEmbed::new()
-              .color(EmbedPalette::new().red)
-              .title(server.name.to_string())
-              .description(":no_entry_sign: **Monica sent invalid structure**")
-              .timestamp(Timestamp::now())
-          );
-          continue;
-        }
-      };
+    self.showing_no_servers.store(false, SeqCst);
 
-      let dss_data = json_value.get("dss");
-      let csg_data = json_value.get("csg");
+    if let Err(y) = mp_info
+      .edit_message(
+        &bot_data.http,
+        mp_info_msg,
+        EditMessage::new()
+          .content(REFRESH_TEXT.replace("{{ refresh.timer }}", &REFRESH_TIMER_SECS.to_string()))
+          .embeds(embeds)
+      )
+      .await
+    {
+      task_err(TASK_NAME, &format!("[monica] Error editing message: {y}"));
+    }
 
-      let (dss, csg): (DssData, CsgData) = {
-        if dss_data.is_none() || csg_data.is_none() {
-          task_err(
-            TASK_NAME,
-            &format!(
-              "[monica:debug_dump] Missing dss/csg fields for \"{}\": dss={dss_data:?}, csg={csg_data:?}",
-              server.name
-            )
-          );
-          embeds.push(
+    Ok(())
+  }
+}
+
+impl Monica {
+  /// Compare the players against the previous refresh and open or close their sessions, returns who joined and left.<br>
+  /// The first refresh after a restart compares against the sessions still open in the database instead.
+  async fn track_sessions(
+    &self,
+    bot_data: &BotData,
+    server_name: &str,
+    players: &[DssPlayer],
+    now: i64
+  ) -> Result<RosterChanges, BotError> {
+    let current = roster_from_players(players);
+    let remembered = self.rosters.get(server_name).map(|r| r.clone());
+    let previous = match remembered {
+      Some(roster) => roster,
+      None => roster_from_sessions(&PlayerSessions::get_open(&bot_data.postgres, server_name).await?, now)
+    };
+
+    let changes = diff_rosters(&previous, &current, now);
+    // Nobody has an open session after a gap in the polling, everyone still on gets a new one without being announced again
+    let ended = self.sessions_ended.contains(server_name);
+    let writes = if ended {
+      diff_rosters(&Roster::new(), &current, now)
+    } else {
+      changes.clone()
+    };
+
+    for left in &writes.left {
+      PlayerSessions::close(&bot_data.postgres, server_name, &left.name, left.at).await?;
+    }
+    for joined in &writes.joined {
+      PlayerSessions {
+        server_name: server_name.to_string(),
+        player_name: joined.name.clone(),
+        is_admin:    joined.is_admin,
+        started_at:  joined.at,
+        ended_at:    None,
+        duration:    None,
+        last_seen:   Some(now)
+      }
+      .open(&bot_data.postgres)
+      .await?;
+    }
+    PlayerSessions::touch(&bot_data.postgres, server_name, now).await?;
+
+    // Only remember the roster once the sessions are stored, so a failed write is retried on the next refresh
+    self.rosters.insert(server_name.to_string(), current);
+    if ended {
+      self.sessions_ended.remove(server_name);
+    }
+
+    Ok(changes)
+  }
+
+  /// End the sessions on servers that haven't been polled for a few refreshes, whether they're inactive, gone or failed to respond.<br>
+  /// Inactive and deleted servers are forgotten, the rest keep their roster so players aren't announced again when it's back.
+  async fn end_unpolled_sessions(
+    &self,
+    bot_data: &BotData,
+    active: &[&MpServers],
+    polled: &[String]
+  ) {
+    self.rosters.retain(|name, _| active.iter().any(|s| s.name == *name));
+    self.sessions_ended.retain(|name| self.rosters.contains_key(name));
+
+    let seen_before = Timestamp::now().unix_timestamp() - (UNPOLLED_GRACE_REFRESHES * REFRESH_TIMER_SECS) as i64;
+    let closed = match PlayerSessions::close_unpolled(&bot_data.postgres, polled, seen_before).await {
+      Ok(closed) => closed,
+      Err(e) => {
+        task_err(TASK_NAME, &format!("[monica] Failed to end the sessions on unpolled servers: {e}"));
+        return;
+      }
+    };
+
+    if !closed.is_empty() {
+      task_info(
+        TASK_NAME,
+        &format!("[monica] Ended {} sessions on servers that couldn't be polled", closed.len())
+      );
+    }
+    for server_name in closed {
+      if self.rosters.contains_key(&server_name) {
+        self.sessions_ended.insert(server_name);
+      }
+    }
+  }
+
+  /// Fetch the server's data from Monica, record its player counts and build its embed
+  async fn refresh_server(
+    &self,
+    bot_data: &BotData,
+    server: &MpServers
+  ) -> Result<ServerRefresh, BotError> {
+    let mut grpc = bot_data.grpc.clone();
+    let request = grpc.fetch_data(FetchRequest {
+      server_name: server.name.clone(),
+      server_ip:   server.ip.clone(),
+      server_code: server.code.clone(),
+      is_active:   server.is_active,
+      fetch_type:  "both".to_string()
+    });
+
+    let data = match timeout(FETCH_TIMEOUT, request).await {
+      Ok(Ok(d)) => {
+        let response_data = d.into_inner().data;
+
+        if response_data.is_empty() {
+          return Ok(
             CreateEmbed::new()
               .color(EmbedPalette::new().red)
               .title(server.name.to_string())
-              .description(":no_entry_sign: **DSS/CSG data missing some fields, check terminal**")
+              .description(":no_entry_sign: **Monica passed empty data!**")
               .timestamp(Timestamp::now())
+              .into()
           );
-          continue;
         }
 
-        if dss_data.unwrap().is_null() || csg_data.unwrap().is_null() {
-          task_info(TASK_NAME, &format!("[monica:debug_dump] Received nullified data from {}", server.name));
-          embeds.push(
-            CreateEmbed::new()
-              .color(EmbedPalette::new().yellow)
-              .title(server.name.to_string())
-              .description(":hourglass: **Server temporarily unavailable**")
-              .footer(CreateEmbedFooter::new(
-                "Please ping Nwero if this still continues for more than a minute!"
-              ))
-              .timestamp(Timestamp::now())
-          );
-          continue;
+        response_data
+      },
+      Ok(Err(e)) => {
+        if e.message().contains("request timed out: deadline has elapsed") {
+          return Ok(ServerRefresh::default());
         }
 
-        match (
-          serde_json::from_value(dss_data.unwrap().clone()),
-          serde_json::from_value(csg_data.unwrap().clone())
-        ) {
-          (Ok(dss), Ok(csg)) => (dss, csg),
-          (Err(d_e), Err(c_e)) => {
-            task_info(TASK_NAME, &format!("[monica:debug_dump] Raw data for {}: {data:?}", server.name));
-            task_err(TASK_NAME, &format!("[monica:debug_dump]      dss: {d_e} | csg: {c_e}"));
-            embeds.push(
-              CreateEmbed::new()
-                .color(EmbedPalette::new().red)
-                .title(server.name.to_string())
-                .description(":no_entry_sign: **Request failed ─ Dead server**")
-                .timestamp(Timestamp::now())
-            );
-            continue;
-          },
-          (..) => {
-            embeds.push(
-              CreateEmbed::new()
-                .color(EmbedPalette::new().yellow)
-                .title(server.name.to_string())
-                .description(":warning: **Empty data**")
-                .timestamp(Timestamp::now())
-            );
-            continue;
-          }
-        }
-      };
+        eprintln!("gRPC[Error] Monica reported an error: {e}");
+        return Ok(
+          CreateEmbed::new()
+            .color(EmbedPalette::new().red)
+            .title(server.name.to_string())
+            .description(":no_entry_sign: **Monica is currently unavailable!**")
+            .timestamp(Timestamp::now())
+            .into()
+        );
+      },
+      Err(_) => {
+        task_err(
+          TASK_NAME,
+          &format!(
+            "[monica] \"{}\" didn't respond within {}s, skipping it",
+            server.name,
+            FETCH_TIMEOUT.as_secs()
+          )
+        );
+        return Ok(ServerRefresh::default());
+      }
+    };
 
-      let used_slots = dss.slots.clone().unwrap().used as i32;
-      let reset_result = MpServers::reset_peak_players(&postgres, server.name.clone()).await?; // Reset peak players count every 72 hours
-      let update_result = MpServers::update_peak_players(&postgres, server.name.clone(), used_slots).await?;
-      MpServers::update_player_data(&postgres, server.name.clone(), used_slots).await?;
+    let json_value: Value = match serde_json::from_str(&data) {
+      Ok(val) => val,
+      Err(e) => {
+        task_err(TASK_NAME, &format!("[monica:debug_dump] Invalid JSON structure: {e}"));
+        return Ok(
+          CreateEmbed::new()
+            .color(EmbedPalette::new().red)
+            .title(server.name.to_string())
+            .description(":no_entry_sign: **Monica sent invalid structure**")
+            .timestamp(Timestamp::now())
+            .into()
+        );
+      }
+    };
 
-      // Server-specific webhook in each channel
-      savegame_settings_webhook(server, ctx.clone(), &json_value).await;
-      // Time drift logger
-      time_drift_webhook(server, ctx.clone(), &json_value).await;
+    let dss_data = json_value.get("dss");
+    let csg_data = json_value.get("csg");
 
-      if !dss.server.clone().unwrap().name.is_empty() && !dss.is_valid() && !csg.is_valid() {
+    let (dss, csg): (DssData, CsgData) = {
+      if dss_data.is_none() || csg_data.is_none() {
         task_err(
           TASK_NAME,
-          &format!("[monica] Partial data received for \"{}\", not displaying in Discord", server.name)
+          &format!(
+            "[monica:debug_dump] Missing dss/csg fields for \"{}\": dss={dss_data:?}, csg={csg_data:?}",
+            server.name
+          )
         );
-        println!("[monica:invalid_data_received_embed] {dss:?}"); // Debug trace, this section occurs when server gets rebooted.
-        embeds.push(
+        return Ok(
           CreateEmbed::new()
             .color(EmbedPalette::new().red)
             .title(server.name.to_string())
-            .description(":no_entry_sign: **Invalid data received**")
+            .description(":no_entry_sign: **DSS/CSG data missing some fields, check terminal**")
             .timestamp(Timestamp::now())
+            .into()
         );
-        continue;
       }
 
-      let peak_players = MpServers::get_peak_players(&postgres, server.name.clone()).await?;
-      if reset_result || update_result {
-        const PEAK_PLRS_TXT: &str = "Peak players value for";
-        if reset_result {
-          task_info(
-            TASK_NAME,
-            &format!("{PEAK_PLRS_TXT} \"{}\" has passed 72 hours and now since reset", server.name)
+      if dss_data.unwrap().is_null() || csg_data.unwrap().is_null() {
+        task_info(TASK_NAME, &format!("[monica:debug_dump] Received nullified data from {}", server.name));
+        return Ok(
+          CreateEmbed::new()
+            .color(EmbedPalette::new().yellow)
+            .title(server.name.to_string())
+            .description(":hourglass: **Server temporarily unavailable**")
+            .footer(CreateEmbedFooter::new(
+              "Please ping Nwero if this still continues for more than a minute!"
+            ))
+            .timestamp(Timestamp::now())
+            .into()
+        );
+      }
+
+      match (
+        serde_json::from_value(dss_data.unwrap().clone()),
+        serde_json::from_value(csg_data.unwrap().clone())
+      ) {
+        (Ok(dss), Ok(csg)) => (dss, csg),
+        (Err(d_e), Err(c_e)) => {
+          task_info(TASK_NAME, &format!("[monica:debug_dump] Raw data for {}: {data:?}", server.name));
+          task_err(TASK_NAME, &format!("[monica:debug_dump]      dss: {d_e} | csg: {c_e}"));
+          return Ok(
+            CreateEmbed::new()
+              .color(EmbedPalette::new().red)
+              .title(server.name.to_string())
+              .description(":no_entry_sign: **Request failed ─ Dead server**")
+              .timestamp(Timestamp::now())
+              .into()
+          );
+        },
+        (..) => {
+          return Ok(
+            CreateEmbed::new()
+              .color(EmbedPalette::new().yellow)
+              .title(server.name.to_string())
+              .description(":warning: **Empty data**")
+              .timestamp(Timestamp::now())
+              .into()
           );
-          cache_servers(&redis, servers.clone()).await?;
-        } else {
-          task_info(TASK_NAME, &format!("{PEAK_PLRS_TXT} \"{}\" has been updated", server.name));
-          cache_servers(&redis, servers.clone()).await?;
         }
       }
+    };
 
-      let players = match dss.slots.clone().unwrap().used {
-        0 => EMPTY_PLAYER_LIST_TEXT.to_string(),
-        _ => playerlist_constructor(dss.slots.clone().unwrap().players)
-      };
+    let used_slots = dss.slots.clone().unwrap().used as i32;
+    let reset_result = MpServers::reset_peak_players(&bot_data.postgres, server.name.clone()).await?; // Reset peak players count every 72 hours
+    let update_result = MpServers::update_peak_players(&bot_data.postgres, server.name.clone(), used_slots).await?;
+    MpServers::update_player_data(&bot_data.postgres, server.name.clone(), used_slots).await?;
+    let now = Timestamp::now().unix_timestamp();
+    // History is a nice-to-have, losing a sample shouldn't take the server off the list
+    let sample = PlayerSamples {
+      server_name: server.name.clone(),
+      timestamp:   now,
+      players:     used_slots
+    };
+    if let Err(e) = sample.record(&bot_data.postgres).await {
+      task_err(TASK_NAME, &format!("[monica:{}] Failed to record the player count: {e}", server.name));
+    }
+    let (changes, polled) = match self
+      .track_sessions(bot_data, &server.name, &dss.slots.clone().unwrap().players, now)
+      .await
+    {
+      Ok(changes) => (changes, true),
+      Err(e) => {
+        task_err(TASK_NAME, &format!("[monica:{}] Failed to track player sessions: {e}", server.name));
+        (RosterChanges::default(), false)
+      }
+    };
+    if server.join_feed && !changes.is_empty() {
+      join_feed_webhook(server, bot_data, &changes).await;
+    }
 
-      let slot_usage = csg.slot_system.map_or_else(
-        || {
-          task_err(TASK_NAME, &format!("[csg:slot_system] Slot system data missing for \"{}\"", server.name));
-          UNKNOWN_SLOT_SYSTEM.to_string()
-        },
-        |slot_system| {
-          slot_system.slot_usage.parse::<i32>().map_or_else(
-            |e| {
-              task_err(TASK_NAME, &format!("[csg:slot_system] Invalid slot usage value: {e}"));
-              UNKNOWN_SLOT_SYSTEM.to_string()
-            },
-            |current_usage| {
-              let formatted_usage = current_usage.to_formatted_string(&Locale::en_AU);
-              let limit_str = CONSOLE_SLOT_LIMIT.to_formatted_string(&Locale::en_AU);
-              format!("**{formatted_usage}**/**{limit_str}**")
-            }
-          )
-        }
-      );
+    let state = ServerState {
+      online:     dss.server.as_ref().is_some_and(|s| !s.name.is_empty()),
+      free_slots: (dss.slots.as_ref().map_or(0, |s| s.capacity as i32) - used_slots).max(0)
+    };
+    // Only a response Monica could answer says whether the server is up, failed fetches leave the last known state alone
+    let previous_state = self.server_states.insert(server.name.clone(), state);
+    notify_subscribers(bot_data, &server.name, previous_state, state, &changes.joined, now).await;
 
-      let time_scale = csg.settings.clone().map_or_else(|| 0.0, |settings| settings.time_scale);
-
-      let embed = CreateEmbed::new()
-        .color(BINARY_PROPERTIES.embed_colors.primary)
-        .title(dss.server.clone().unwrap().name.to_string())
-        .description(players)
-        .fields(vec![
-          (
-            "Time",
-            format!("{} ({time_scale}x)", format_daytime(dss.server.clone().unwrap().day_time)),
-            true
-          ),
-          ("Map", dss.server.clone().unwrap().map_name, true),
-          ("Slot Usage", slot_usage, true),
-        ])
-        .author(CreateEmbedAuthor::new(format!(
-          "{}/{} ({peak_players})",
-          dss.slots.clone().unwrap().used,
-          dss.slots.clone().unwrap().capacity
-        )))
-        .footer(CreateEmbedFooter::new(format!(
-          "Autosave: {} mins ∙ Version: {}",
-          csg.settings.unwrap().auto_save_interval,
-          dss.server.clone().unwrap().version
-        )))
-        .timestamp(Timestamp::now());
-
-      let embed = if dss.server.unwrap().name.is_empty() {
-        CreateEmbed::new()
+    // Server-specific webhook in each channel
+    savegame_settings_webhook(server, bot_data, &json_value).await;
+    // Time drift logger
+    time_drift_webhook(server, bot_data, &json_value, &self.day_times).await;
+
+    if !dss.server.clone().unwrap().name.is_empty() && !dss.is_valid() && !csg.is_valid() {
+      task_err(
+        TASK_NAME,
+        &format!("[monica] Partial data received for \"{}\", not displaying in Discord", server.name)
+      );
+      println!("[monica:invalid_data_received_embed] {dss:?}"); // Debug trace, this section occurs when server gets rebooted.
+      return Ok(ServerRefresh {
+        polled,
+        ..CreateEmbed::new()
           .color(EmbedPalette::new().red)
-          .title(format!("{} is offline", server.name))
+          .title(server.name.to_string())
+          .description(":no_entry_sign: **Invalid data received**")
           .timestamp(Timestamp::now())
-      } else {
-        embed
-      };
-
-      embeds.push(embed);
+          .into()
+      });
     }
 
-    if embeds.is_empty() && !showing_no_servers {
-      task_info(TASK_NAME, "[monica] No embeds to update message with");
-      if let Err(y) = mp_info
-        .edit_message(&ctx.http, mp_info_msg, EditMessage::new().content(NO_SERVERS_TEXT).embeds(vec![]))
-        .await
-      {
-        task_err(TASK_NAME, &format!("[monica] Error editing message: {y}"));
+    let peak_players = MpServers::get_peak_players(&bot_data.postgres, server.name.clone()).await?;
+    let peak_changed = reset_result || update_result;
+    if peak_changed {
+      const PEAK_PLRS_TXT: &str = "Peak players value for";
+      if reset_result {
+        task_info(
+          TASK_NAME,
+          &format!("{PEAK_PLRS_TXT} \"{}\" has passed 72 hours and now since reset", server.name)
+        );
+      } else {
+        task_info(TASK_NAME, &format!("{PEAK_PLRS_TXT} \"{}\" has been updated", server.name));
       }
-      showing_no_servers = true;
-      continue;
     }
 
-    showing_no_servers = false;
+    let players = match dss.slots.clone().unwrap().used {
+      0 => EMPTY_PLAYER_LIST_TEXT.to_string(),
+      _ => playerlist_constructor(dss.slots.clone().unwrap().players)
+    };
 
-    if let Err(y) = mp_info
-      .edit_message(
-        &ctx.http,
-        mp_info_msg,
-        EditMessage::new()
-          .content(REFRESH_TEXT.replace("{{ refresh.timer }}", &REFRESH_TIMER_SECS.to_string()))
-          .embeds(embeds)
-      )
-      .await
-    {
-      task_err(TASK_NAME, &format!("[monica] Error editing message: {y}"));
-      continue;
-    }
+    let slot_usage = csg.slot_system.map_or_else(
+      || {
+        task_err(TASK_NAME, &format!("[csg:slot_system] Slot system data missing for \"{}\"", server.name));
+        UNKNOWN_SLOT_SYSTEM.to_string()
+      },
+      |slot_system| {
+        slot_system.slot_usage.parse::<i32>().map_or_else(
+          |e| {
+            task_err(TASK_NAME, &format!("[csg:slot_system] Invalid slot usage value: {e}"));
+            UNKNOWN_SLOT_SYSTEM.to_string()
+          },
+          |current_usage| {
+            let formatted_usage = current_usage.to_formatted_string(&Locale::en_AU);
+            let limit_str = CONSOLE_SLOT_LIMIT.to_formatted_string(&Locale::en_AU);
+            format!("**{formatted_usage}**/**{limit_str}**")
+          }
+        )
+      }
+    );
+
+    let time_scale = csg.settings.clone().map_or_else(|| 0.0, |settings| settings.time_scale);
+
+    let embed = CreateEmbed::new()
+      .color(BINARY_PROPERTIES.embed_colors.primary)
+      .title(dss.server.clone().unwrap().name.to_string())
+      .description(players)
+      .fields(vec![
+        (
+          "Time",
+          format!("{} ({time_scale}x)", format_daytime(dss.server.clone().unwrap().day_time)),
+          true
+        ),
+        ("Map", dss.server.clone().unwrap().map_name, true),
+        ("Slot Usage", slot_usage, true),
+      ])
+      .author(CreateEmbedAuthor::new(format!(
+        "{}/{} ({peak_players})",
+        dss.slots.clone().unwrap().used,
+        dss.slots.clone().unwrap().capacity
+      )))
+      .footer(CreateEmbedFooter::new(format!(
+        "Autosave: {} mins ∙ Version: {}",
+        csg.settings.unwrap().auto_save_interval,
+        dss.server.clone().unwrap().version
+      )))
+      .timestamp(Timestamp::now());
+
+    let embed = if dss.server.unwrap().name.is_empty() {
+      CreateEmbed::new()
+        .color(EmbedPalette::new().red)
+        .title(format!("{} is offline", server.name))
+        .timestamp(Timestamp::now())
+    } else {
+      embed
+    };
+
+    Ok(ServerRefresh {
+      embed: Some(embed),
+      peak_changed,
+      polled
+    })
   }
 }
 
@@ -748,7 +960,7 @@ fn icon_factory(player: &DssPlayer) -> String {
 
 async fn savegame_settings_webhook(
   server: &MpServers,
-  ctx: Arc<Context>,
+  bot_data: &BotData,
   data: &Value
 ) {
   if !server.is_active {
@@ -781,7 +993,7 @@ async fn savegame_settings_webhook(
     }
   };
 
-  let redis = &ctx.data::<BotData>().redis;
+  let redis = &bot_data.redis;
   let cache_key = format!("{TASK_NAME}:savegame_settings:{}", server.name);
   let csg_settings__ = csg.settings.is_some();
 
@@ -859,8 +1071,8 @@ async fn savegame_settings_webhook(
     return;
   }
 
-  let bot_http = Http::new(discord_token().await);
-  let hookdb = match Webhooks::get_hooks(&ctx.data::<BotData>().postgres).await {
+  let bot_http = &bot_data.http;
+  let hookdb = match Webhooks::get_hooks(&bot_data.postgres).await {
     Ok(hooks) => hooks,
     Err(e) => {
       task_err(TASK_NAME, &format!("[savegame_settings_webhook] Failed to get webhooks: {e}"));
@@ -885,7 +1097,7 @@ async fn savegame_settings_webhook(
       }
     };
 
-    match Webhook::from_id_with_token(&bot_http, webhook_id, &hook.token).await {
+    match Webhook::from_id_with_token(bot_http, webhook_id, &hook.token).await {
       Ok(webhook) => {
         let embed = CreateEmbed::default()
           .color(if csg_settings__ {
@@ -899,7 +1111,7 @@ async fn savegame_settings_webhook(
           .timestamp(Timestamp::now());
 
         match webhook
-          .edit_message(&bot_http, message_id, EditWebhookMessage::default().content(String::new()).embed(embed))
+          .edit_message(bot_http, message_id, EditWebhookMessage::default().content(String::new()).embed(embed))
           .await
         {
           Ok(_) => {
@@ -928,10 +1140,99 @@ async fn savegame_settings_webhook(
   }
 }
 
+/// Post everyone who joined or left since the last refresh into the server's thread as a single message
+async fn join_feed_webhook(
+  server: &MpServers,
+  bot_data: &BotData,
+  changes: &RosterChanges
+) {
+  /// Leaves some room under the embed description limit for the overflow line
+  const DESCRIPTION_LIMIT: usize = 3900;
+
+  let mut events: Vec<_> = changes
+    .joined
+    .iter()
+    .map(|e| (e, true))
+    .chain(changes.left.iter().map(|e| (e, false)))
+    .collect();
+  // A rejoin's leave is backdated to the same moment as its join, so leaves go first on ties
+  events.sort_by_key(|(e, joined)| (e.at, *joined));
+
+  let mut description = String::new();
+  for (shown, (e, joined)) in events.iter().enumerate() {
+    let admin = if e.is_admin { " (admin)" } else { "" };
+    let line = if *joined {
+      format!(":inbox_tray: **{}**{admin} joined <t:{}:T>\n", e.name, e.at)
+    } else {
+      let stayed = match e.uptime {
+        0 => "less than a minute".to_string(),
+        uptime => format_player_uptime(uptime)
+      };
+      format!(":outbox_tray: **{}**{admin} left <t:{}:T> after {stayed}\n", e.name, e.at)
+    };
+
+    if description.len() + line.len() > DESCRIPTION_LIMIT {
+      description.push_str(&format!("*...and {} more*", events.len() - shown));
+      break;
+    }
+    description.push_str(&line);
+  }
+
+  let bot_http = &bot_data.http;
+  let hookdb = match Webhooks::get_hooks(&bot_data.postgres).await {
+    Ok(hooks) => hooks,
+    Err(e) => {
+      task_err(TASK_NAME, &format!("[join_feed_webhook] Failed to get webhooks: {e}"));
+      return;
+    }
+  };
+
+  for hook in hookdb.iter().filter(|h| h.name == server.name) {
+    // Ids of zero aren't valid snowflakes and would panic on the way in
+    let Some(webhook_id) = hook.id.parse::<u64>().ok().filter(|&id| id != 0) else {
+      task_err(TASK_NAME, &format!("[join_feed_webhook:{}] Invalid webhook ID: {}", server.name, hook.id));
+      continue;
+    };
+
+    let Some(thread_id) = hook.thread_id.parse::<u64>().ok().filter(|&id| id != 0) else {
+      task_err(
+        TASK_NAME,
+        &format!("[join_feed_webhook:{}] Invalid thread ID: {}", server.name, hook.thread_id)
+      );
+      continue;
+    };
+
+    let webhook = match Webhook::from_id_with_token(bot_http, WebhookId::new(webhook_id), &hook.token).await {
+      Ok(webhook) => webhook,
+      Err(e) => {
+        task_err(TASK_NAME, &format!("[join_feed_webhook:{}] Webhook doesn't exist: {e}", server.name));
+        continue;
+      }
+    };
+
+    if let Err(e) = webhook
+      .execute(
+        bot_http,
+        false,
+        ExecuteWebhook::new().in_thread(ThreadId::new(thread_id)).embed(
+          CreateEmbed::new()
+            .color(BINARY_PROPERTIES.embed_colors.primary)
+            .title(format!("Joins and leaves - {}", server.name))
+            .description(description.clone())
+        )
+      )
+      .await
+    {
+      task_err(TASK_NAME, &format!("[join_feed_webhook:{}] Failed to post the feed: {e}", server.name));
+    }
+  }
+}
+
 async fn time_drift_webhook(
   server: &MpServers,
-  ctx: Arc<Context>,
-  data: &Value
+  bot_data: &BotData,
+  data: &Value,
+  day_times: &DashMap<String, i32>
 ) {
   if !server.is_active {
     return;
@@ -978,17 +1279,14 @@ async fn time_drift_webhook(
   /// 17:35
   const EVENING: i32 = 63333710;
 
-  let redis = ctx.data::<crate::BotData>().redis.clone();
+  let redis = &bot_data.redis;
   let redis_webhook_sent = format!("{TASK_NAME}:time_drift:{}:webhook_sent", server.name);
 
   let current_time = dss.server.as_ref().map(|s| s.day_time).unwrap_or_default();
-  let previous_time = PREVIOUS_DAY_TIME.load(Acquire);
-  if previous_time == 0 {
-    PREVIOUS_DAY_TIME.store(current_time, Release);
+  // First sighting of the server, nothing to compare against yet
+  let Some(previous_time) = day_times.insert(server.name.clone(), current_time).filter(|t| *t != 0) else {
     return;
-  }
-
-  PREVIOUS_DAY_TIME.store(current_time, Release);
+  };
 
   // Check if webhook was already sent
   match redis.get(&redis_webhook_sent).await {
@@ -1021,8 +1319,8 @@ async fn time_drift_webhook(
     }
   }
 
-  let bot_http = Http::new(discord_token().await);
-  let hookdb = match Webhooks::get_hooks(&ctx.data::<BotData>().postgres).await {
+  let bot_http = &bot_data.http;
+  let hookdb = match Webhooks::get_hooks(&bot_data.postgres).await {
     Ok(hooks) => hooks,
     Err(e) => {
       task_err(TASK_NAME, &format!("[time_drift_webhook] Failed to get webhooks: {e}"));
@@ -1036,7 +1334,7 @@ async fn time_drift_webhook(
         continue;
       }
 
-      let webhook = match Webhook::from_id_with_token(&bot_http, WebhookId::new(hook.id.parse().unwrap_or_default()), &hook.token).await {
+      let webhook = match Webhook::from_id_with_token(bot_http, WebhookId::new(hook.id.parse().unwrap_or_default()), &hook.token).await {
         Ok(webhook) => webhook,
         Err(e) => {
           task_err(TASK_NAME, &format!("[time_drift_webhook:{}] Webhook doesn't exist: {e}", server.name));
@@ -1052,7 +1350,7 @@ async fn time_drift_webhook(
 
       match webhook
         .execute(
-          &bot_http,
+          bot_http,
           true,
           ExecuteWebhook::new()
             .in_thread(ThreadId::new(hook.thread_id.parse::<u64>().unwrap_or_default()))
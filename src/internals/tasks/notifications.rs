@@ -0,0 +1,177 @@
+use {
+  super::{
+    super::config::BINARY_PROPERTIES,
+    monica::TASK_NAME,
+    sessions::SessionEvent,
+    task_err
+  },
+  crate::{
+    BotData,
+    controllers::sql::MpSubscriptions
+  }
+};
+
+use poise::serenity_prelude::{
+  CreateEmbed,
+  CreateEmbedFooter,
+  CreateMessage,
+  UserId
+};
+
+/// How long a subscription stays quiet after it's been sent, so a flapping server or player can't flood someone's DMs
+const NOTIFY_COOLDOWN_SECS: i64 = 1800;
+
+/// Whether a server was up and how many slots it had free on a refresh
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ServerState {
+  pub online:     bool,
+  pub free_slots: i32
+}
+
+/// Whether the server was down on the previous refresh and is up now
+pub fn came_online(
+  previous: Option<ServerState>,
+  current: ServerState
+) -> bool {
+  previous.is_some_and(|p| !p.online) && current.online
+}
+
+/// Whether the free slots fell below the threshold since the previous refresh, while the server stayed up
+pub fn dropped_below(
+  previous: Option<ServerState>,
+  current: ServerState,
+  threshold: i32
+) -> bool {
+  previous.is_some_and(|p| p.online && p.free_slots >= threshold) && current.online && current.free_slots < threshold
+}
+
+/// DM everyone subscribed to the players that joined or to the server's state changing.<br>
+/// Nothing is sent for a server until it has been seen on a previous refresh.
+pub async fn notify_subscribers(
+  bot_data: &BotData,
+  server_name: &str,
+  previous: Option<ServerState>,
+  current: ServerState,
+  joined: &[SessionEvent],
+  now: i64
+) {
+  let postgres = &bot_data.postgres;
+
+  if !joined.is_empty() {
+    let names: Vec<String> = joined.iter().map(|e| e.name.clone()).collect();
+    match MpSubscriptions::get_for_targets(postgres, MpSubscriptions::PLAYER, &names).await {
+      Ok(subs) => {
+        for sub in subs {
+          if let Some(event) = joined.iter().find(|e| e.name.eq_ignore_ascii_case(&sub.target)) {
+            send(bot_data, &sub, now, format!("**{}** just joined **{server_name}**", event.name)).await;
+          }
+        }
+      },
+      Err(e) => task_err(
+        TASK_NAME,
+        &format!("[notify_subscribers:{server_name}] Failed to get player subscriptions: {e}")
+      )
+    }
+  }
+
+  // Thresholds are per subscription, skip the lookup when none of them could have been crossed
+  let back_online = came_online(previous, current);
+  let slots_fell = previous.is_some_and(|p| p.online && current.online && current.free_slots < p.free_slots);
+  if !back_online && !slots_fell {
+    return;
+  }
+
+  let subs = match MpSubscriptions::get_for_targets(postgres, MpSubscriptions::SERVER, &[server_name.to_string()]).await {
+    Ok(subs) => subs,
+    Err(e) => {
+      task_err(
+        TASK_NAME,
+        &format!("[notify_subscribers:{server_name}] Failed to get server subscriptions: {e}")
+      );
+      return;
+    }
+  };
+
+  for sub in subs {
+    let message = if back_online {
+      format!("**{server_name}** is back online with {} free slots", current.free_slots)
+    } else if sub.free_slots.is_some_and(|n| dropped_below(previous, current, n)) {
+      format!("**{server_name}** is down to {} free slots", current.free_slots)
+    } else {
+      continue;
+    };
+
+    send(bot_data, &sub, now, message).await;
+  }
+}
+
+async fn send(
+  bot_data: &BotData,
+  sub: &MpSubscriptions,
+  now: i64,
+  message: String
+) {
+  match sub.claim(&bot_data.postgres, now, NOTIFY_COOLDOWN_SECS).await {
+    Ok(true) => (),
+    Ok(false) => return,
+    Err(e) => {
+      task_err(TASK_NAME, &format!("[notify_subscribers] Failed to claim subscription {}: {e}", sub.id));
+      return;
+    }
+  }
+
+  let embed = CreateEmbed::new()
+    .color(BINARY_PROPERTIES.embed_colors.primary)
+    .description(message)
+    .footer(CreateEmbedFooter::new("Use /mp notify remove to stop these"));
+
+  // Closed DMs aren't worth more than a log line, the cooldown still applies so it isn't retried every refresh
+  if let Err(e) = UserId::new(sub.user_id as u64)
+    .direct_message(&bot_data.http, CreateMessage::new().embed(embed))
+    .await
+  {
+    task_err(TASK_NAME, &format!("[notify_subscribers] Couldn't DM {}: {e}", sub.user_id));
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{
+    ServerState,
+    came_online,
+    dropped_below
+  };
+
+  fn state(
+    online: bool,
+    free_slots: i32
+  ) -> ServerState {
+    ServerState { online, free_slots }
+  }
+
+  #[test]
+  fn first_sighting_is_not_a_change() {
+    assert!(!came_online(None, state(true, 10)));
+    assert!(!dropped_below(None, state(true, 1), 5));
+  }
+
+  #[test]
+  fn coming_back_online() {
+    assert!(came_online(Some(state(false, 0)), state(true, 16)));
+    assert!(!came_online(Some(state(true, 16)), state(true, 16)));
+    assert!(!came_online(Some(state(true, 16)), state(false, 0)));
+  }
+
+  #[test]
+  fn slots_only_trigger_when_crossing_the_threshold() {
+    assert!(dropped_below(Some(state(true, 5)), state(true, 4), 5));
+    assert!(!dropped_below(Some(state(true, 4)), state(true, 3), 5));
+    assert!(!dropped_below(Some(state(true, 6)), state(true, 5), 5));
+  }
+
+  #[test]
+  fn going_offline_is_not_running_out_of_slots() {
+    assert!(!dropped_below(Some(state(true, 10)), state(false, 0), 5));
+    assert!(!dropped_below(Some(state(false, 0)), state(true, 2), 5));
+  }
+}
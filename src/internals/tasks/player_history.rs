@@ -0,0 +1,52 @@
+use {
+  super::{
+    super::scheduler::TaskScheduler,
+    task_info
+  },
+  crate::{
+    BotData,
+    BotError,
+    controllers::sql::{
+      PlayerSamples,
+      Settings
+    }
+  }
+};
+
+use {
+  poise::serenity_prelude::{
+    Timestamp,
+    async_trait
+  },
+  std::sync::Arc
+};
+
+static TASK_NAME: &str = "Player History";
+
+/// Rolls the player samples Monica records up into hourly and daily aggregates and drops what's past retention
+pub struct PlayerHistory;
+
+#[async_trait]
+impl TaskScheduler for PlayerHistory {
+  fn name(&self) -> &'static str { TASK_NAME }
+
+  fn interval_secs(&self) -> u64 { 3600 }
+
+  async fn main_loop(
+    &self,
+    bot_data: Arc<BotData>
+  ) -> Result<(), BotError> {
+    let now = Timestamp::now().unix_timestamp();
+
+    PlayerSamples::downsample(&bot_data.postgres, now).await?;
+
+    let retention = Settings::get_player_retention(&bot_data.postgres).await?;
+    let removed = PlayerSamples::prune(&bot_data.postgres, now, retention).await?;
+
+    if removed > 0 {
+      task_info(TASK_NAME, &format!("Pruned {removed} rows of player history"));
+    }
+
+    Ok(())
+  }
+}
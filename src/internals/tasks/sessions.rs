@@ -0,0 +1,212 @@
+use {
+  super::monica::DssPlayer,
+  crate::controllers::sql::PlayerSessions,
+  std::collections::HashMap
+};
+
+/// A player as seen on a single poll
+#[derive(Debug, Clone, PartialEq)]
+pub struct OnlinePlayer {
+  pub is_admin: bool,
+  /// Minutes since they joined, as reported by the server
+  pub uptime:   i32
+}
+
+/// Everyone on a server at the time of a poll, keyed by player name
+pub type Roster = HashMap<String, OnlinePlayer>;
+
+/// Player joining or leaving a server
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionEvent {
+  pub name:     String,
+  pub is_admin: bool,
+  /// Unix timestamp
+  pub at:       i64,
+  /// Minutes the player had been on for when they were last seen
+  pub uptime:   i32
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RosterChanges {
+  pub joined: Vec<SessionEvent>,
+  pub left:   Vec<SessionEvent>
+}
+
+impl RosterChanges {
+  pub fn is_empty(&self) -> bool { self.joined.is_empty() && self.left.is_empty() }
+}
+
+pub fn roster_from_players(players: &[DssPlayer]) -> Roster {
+  players
+    .iter()
+    .filter(|p| p.is_used.unwrap_or(false))
+    .filter_map(|p| {
+      p.name.clone().map(|name| {
+        (
+          name,
+          OnlinePlayer {
+            is_admin: p.is_admin.unwrap_or(false),
+            uptime:   p.uptime.unwrap_or(0)
+          }
+        )
+      })
+    })
+    .collect()
+}
+
+/// Rebuild a roster from the sessions left open in the database, used when the bot hasn't polled the server yet
+pub fn roster_from_sessions(
+  sessions: &[PlayerSessions],
+  now: i64
+) -> Roster {
+  sessions
+    .iter()
+    .map(|s| {
+      (
+        s.player_name.clone(),
+        OnlinePlayer {
+          is_admin: s.is_admin,
+          uptime:   ((now - s.started_at) / 60) as i32
+        }
+      )
+    })
+    .collect()
+}
+
+/// Work out who joined and left between two polls.<br>
+/// Joins are backdated by the player's uptime, and a player whose uptime went down
+/// must have left and rejoined since the last poll, so they get both.
+pub fn diff_rosters(
+  previous: &Roster,
+  current: &Roster,
+  now: i64
+) -> RosterChanges {
+  let mut changes = RosterChanges::default();
+
+  for (name, player) in current {
+    let joined_at = now - player.uptime as i64 * 60;
+
+    match previous.get(name) {
+      Some(before) if player.uptime >= before.uptime => continue,
+      Some(before) => changes.left.push(SessionEvent {
+        name:     name.clone(),
+        is_admin: before.is_admin,
+        at:       joined_at,
+        uptime:   before.uptime
+      }),
+      None => ()
+    }
+
+    changes.joined.push(SessionEvent {
+      name:     name.clone(),
+      is_admin: player.is_admin,
+      at:       joined_at,
+      uptime:   player.uptime
+    });
+  }
+
+  for (name, player) in previous {
+    if !current.contains_key(name) {
+      changes.left.push(SessionEvent {
+        name:     name.clone(),
+        is_admin: player.is_admin,
+        at:       now,
+        uptime:   player.uptime
+      });
+    }
+  }
+
+  changes.joined.sort_by(|a, b| a.name.cmp(&b.name));
+  changes.left.sort_by(|a, b| a.name.cmp(&b.name));
+  changes
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{
+    OnlinePlayer,
+    PlayerSessions,
+    Roster,
+    SessionEvent,
+    diff_rosters,
+    roster_from_sessions
+  };
+
+  const NOW: i64 = 1_700_000_000;
+
+  fn roster(players: &[(&str, i32)]) -> Roster {
+    players
+      .iter()
+      .map(|(name, uptime)| {
+        (
+          name.to_string(),
+          OnlinePlayer {
+            is_admin: false,
+            uptime:   *uptime
+          }
+        )
+      })
+      .collect()
+  }
+
+  fn event(
+    name: &str,
+    at: i64,
+    uptime: i32
+  ) -> SessionEvent {
+    SessionEvent {
+      name: name.to_string(),
+      is_admin: false,
+      at,
+      uptime
+    }
+  }
+
+  #[test]
+  fn unchanged_roster_has_no_changes() {
+    let before = roster(&[("Alice", 10), ("Bob", 0)]);
+    let after = roster(&[("Alice", 11), ("Bob", 0)]);
+
+    assert!(diff_rosters(&before, &after, NOW).is_empty());
+  }
+
+  #[test]
+  fn joins_are_backdated_by_uptime() {
+    let changes = diff_rosters(&roster(&[]), &roster(&[("Alice", 0), ("Bob", 90)]), NOW);
+
+    assert_eq!(changes.joined, vec![event("Alice", NOW, 0), event("Bob", NOW - 90 * 60, 90)]);
+    assert!(changes.left.is_empty());
+  }
+
+  #[test]
+  fn missing_players_left_at_the_poll() {
+    let changes = diff_rosters(&roster(&[("Alice", 10), ("Bob", 5)]), &roster(&[("Alice", 11)]), NOW);
+
+    assert!(changes.joined.is_empty());
+    assert_eq!(changes.left, vec![event("Bob", NOW, 5)]);
+  }
+
+  #[test]
+  fn uptime_reset_is_a_rejoin() {
+    let changes = diff_rosters(&roster(&[("Alice", 45)]), &roster(&[("Alice", 2)]), NOW);
+
+    assert_eq!(changes.left, vec![event("Alice", NOW - 2 * 60, 45)]);
+    assert_eq!(changes.joined, vec![event("Alice", NOW - 2 * 60, 2)]);
+  }
+
+  #[test]
+  fn seeded_roster_matches_the_live_one() {
+    let open = vec![PlayerSessions {
+      server_name: "Server".to_string(),
+      player_name: "Alice".to_string(),
+      is_admin:    false,
+      started_at:  NOW - 30 * 60 - 20,
+      ended_at:    None,
+      duration:    None,
+      last_seen:   Some(NOW)
+    }];
+
+    let seeded = roster_from_sessions(&open, NOW);
+    assert!(diff_rosters(&seeded, &roster(&[("Alice", 30)]), NOW).is_empty());
+  }
+}
@@ -0,0 +1,526 @@
+use crate::{
+  BotError,
+  controllers::sql::{
+    ProhibitedWords,
+    WordAllowlist
+  }
+};
+
+use {
+  aho_corasick::AhoCorasick,
+  regex::{
+    RegexSet,
+    RegexSetBuilder
+  },
+  sqlx::PgPool,
+  std::{
+    collections::{
+      HashMap,
+      HashSet
+    },
+    str::FromStr
+  },
+  unicode_normalization::UnicodeNormalization
+};
+
+/// Endings tolerated on exact matches so plurals and tenses don't need their own entry
+const SUFFIXES: [&str; 3] = ["ing", "ed", "s"];
+/// Runs of single characters this long get glued back together, e.g. `w o r d` or `w.o.r.d`
+const MIN_RUN: usize = 3;
+/// Keeps a bad regex entry from eating all the memory
+const REGEX_SIZE_LIMIT: usize = 1 << 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, poise::ChoiceParameter)]
+pub enum MatchMode {
+  /// Whole word, with common endings and trailing digits tolerated
+  Exact,
+  /// Anywhere, including inside other words
+  Substring,
+  /// `*` for any number of characters and `?` for one, matched per word
+  Wildcard,
+  /// Regex against the normalised message
+  Regex
+}
+
+impl MatchMode {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      Self::Exact => "Exact",
+      Self::Substring => "Substring",
+      Self::Wildcard => "Wildcard",
+      Self::Regex => "Regex"
+    }
+  }
+}
+
+impl FromStr for MatchMode {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "Exact" => Ok(Self::Exact),
+      "Substring" => Ok(Self::Substring),
+      "Wildcard" => Ok(Self::Wildcard),
+      "Regex" => Ok(Self::Regex),
+      _ => Err(format!("`{s}` is not a valid match mode"))
+    }
+  }
+}
+
+/// Combining marks used for zalgo text
+pub fn is_combining(c: char) -> bool {
+  matches!(
+    c as u32,
+    0x0300..=0x036F | 0x0483..=0x0489 | 0x1AB0..=0x1AFF | 0x1DC0..=0x1DFF | 0x20D0..=0x20FF | 0xFE20..=0xFE2F
+  )
+}
+
+/// Zero-width and formatting characters that render as nothing
+pub fn is_invisible(c: char) -> bool { matches!(c as u32, 0x200B..=0x200F | 0x202A..=0x202E | 0x2060..=0x2064 | 0xFEFF) || c.is_control() }
+
+/// Fold the Cyrillic and Greek lookalikes NFKC leaves alone into their Latin counterparts
+pub fn fold_confusable(c: char) -> char {
+  match c {
+    'а' | 'α' => 'a',
+    'в' | 'β' => 'b',
+    'с' | 'ϲ' => 'c',
+    'ԁ' => 'd',
+    'е' | 'ё' | 'ε' => 'e',
+    'ɡ' => 'g',
+    'н' | 'һ' => 'h',
+    'і' | 'ї' | 'ι' | 'ı' => 'i',
+    'ј' => 'j',
+    'к' | 'κ' => 'k',
+    'м' => 'm',
+    'η' | 'п' => 'n',
+    'о' | 'ο' | 'σ' => 'o',
+    'р' | 'ρ' => 'p',
+    'ԛ' => 'q',
+    'г' => 'r',
+    'ѕ' => 's',
+    'т' | 'τ' => 't',
+    'υ' | 'ц' => 'u',
+    'ν' => 'v',
+    'ш' | 'ω' => 'w',
+    'х' | 'χ' => 'x',
+    'у' | 'γ' => 'y',
+    _ => c
+  }
+}
+
+fn is_leet_symbol(c: char) -> bool { matches!(c, '@' | '$' | '!' | '|' | '+') }
+
+fn unleet(c: char) -> char {
+  match c {
+    '4' | '@' => 'a',
+    '8' => 'b',
+    '3' => 'e',
+    '9' => 'g',
+    '1' | '!' => 'i',
+    '|' => 'l',
+    '0' => 'o',
+    '5' | '$' => 's',
+    '7' | '+' => 't',
+    _ => c
+  }
+}
+
+/// NFKC, strip zalgo and invisible characters, lowercase and fold lookalikes
+fn fold(text: &str) -> String {
+  text
+    .nfkc()
+    .filter(|c| !is_combining(*c) && !is_invisible(*c))
+    .flat_map(char::to_lowercase)
+    .map(fold_confusable)
+    .collect()
+}
+
+/// Split into words, leet symbols stay attached since they're part of the word
+fn tokenise(folded: &str) -> impl Iterator<Item = &str> {
+  folded
+    .split(|c: char| !c.is_alphanumeric() && !is_leet_symbol(c))
+    .filter(|t| !t.is_empty())
+}
+
+/// Run the entry through the same pipeline as messages, so `W0rd` and `word` are the same entry
+fn normalise_entry(word: &str) -> String {
+  let folded = fold(word);
+  tokenise(&folded)
+    .map(|t| t.chars().map(unleet).collect::<String>())
+    .collect::<Vec<_>>()
+    .join(" ")
+}
+
+fn wildcard_pattern(word: &str) -> String {
+  let folded = fold(word);
+  let words: Vec<String> = folded
+    .split(|c: char| !c.is_alphanumeric() && !is_leet_symbol(c) && c != '*' && c != '?')
+    .filter(|t| !t.is_empty())
+    .map(|t| {
+      t.chars()
+        .map(|c| match c {
+          '*' => "[^ ]*".to_string(),
+          '?' => "[^ ]".to_string(),
+          c => regex::escape(&unleet(c).to_string())
+        })
+        .collect()
+    })
+    .collect();
+
+  format!("(?:^| ){}(?: |$)", words.join(" "))
+}
+
+/// Check an entry before it's added to the list
+pub fn validate(
+  word: &str,
+  mode: MatchMode
+) -> Result<(), String> {
+  match mode {
+    MatchMode::Regex => RegexSetBuilder::new([word])
+      .case_insensitive(true)
+      .size_limit(REGEX_SIZE_LIMIT)
+      .build()
+      .map(|_| ())
+      .map_err(|e| format!("Invalid regex: {e}")),
+    MatchMode::Wildcard if !word.contains(['*', '?']) => Err("Wildcard entries need at least one `*` or `?`".to_string()),
+    _ if normalise_entry(word.trim_matches(['*', '?'])).is_empty() => Err("Entry has nothing left to match after normalisation".to_string()),
+    _ => Ok(())
+  }
+}
+
+/// Normalised view of a message
+struct Scan {
+  /// Every word joined by spaces with padding on both ends, allowlisted words are left out
+  text:       String,
+  /// Individual words, the variant with outer digits and symbols trimmed and glued runs
+  candidates: Vec<String>
+}
+
+/// Prohibited word matcher that sees through fancy fonts, lookalike letters, leetspeak,
+/// zero-width characters and spaced out letters.<br>
+/// Words on the allowlist are skipped, so `Scunthorpe` doesn't trip a substring entry.
+#[derive(Default)]
+pub struct WordFilter {
+  /// Original entries, indexed by the matchers below
+  words:         Vec<String>,
+  exact:         HashMap<String, usize>,
+  /// Exact entries spanning several words, padded with spaces
+  exact_phrases: Vec<(String, usize)>,
+  substrings:    Option<(AhoCorasick, Vec<usize>)>,
+  wildcards:     Option<(RegexSet, Vec<usize>)>,
+  regexes:       Option<(RegexSet, Vec<usize>)>,
+  allowlist:     HashSet<String>
+}
+
+impl WordFilter {
+  pub fn new(
+    entries: &[(String, MatchMode)],
+    allowlist: &[String]
+  ) -> Self {
+    let mut filter = Self {
+      allowlist: allowlist.iter().map(|w| normalise_entry(w)).filter(|w| !w.is_empty()).collect(),
+      ..Default::default()
+    };
+
+    let mut substrings = (Vec::new(), Vec::new());
+    let mut wildcards = (Vec::new(), Vec::new());
+    let mut regexes = (Vec::new(), Vec::new());
+
+    for (word, mode) in entries {
+      if let Err(e) = validate(word, *mode) {
+        eprintln!("WordFilter[Error] Skipping \"{word}\": {e}");
+        continue;
+      }
+
+      let idx = filter.words.len();
+      filter.words.push(word.clone());
+
+      match mode {
+        MatchMode::Exact => {
+          let pattern = normalise_entry(word);
+          if pattern.contains(' ') {
+            filter.exact_phrases.push((format!(" {pattern} "), idx));
+          } else {
+            filter.exact.insert(pattern, idx);
+          }
+        },
+        MatchMode::Substring => {
+          substrings.0.push(normalise_entry(word));
+          substrings.1.push(idx);
+        },
+        MatchMode::Wildcard => {
+          wildcards.0.push(wildcard_pattern(word));
+          wildcards.1.push(idx);
+        },
+        MatchMode::Regex => {
+          regexes.0.push(word.clone());
+          regexes.1.push(idx);
+        }
+      }
+    }
+
+    if !substrings.0.is_empty() {
+      match AhoCorasick::new(&substrings.0) {
+        Ok(ac) => filter.substrings = Some((ac, substrings.1)),
+        Err(e) => eprintln!("WordFilter[Error] Failed to build the substring matcher: {e}")
+      }
+    }
+
+    for (patterns, slot) in [(wildcards, &mut filter.wildcards), (regexes, &mut filter.regexes)] {
+      if patterns.0.is_empty() {
+        continue;
+      }
+
+      match RegexSetBuilder::new(&patterns.0)
+        .case_insensitive(true)
+        .size_limit(REGEX_SIZE_LIMIT)
+        .build()
+      {
+        Ok(set) => *slot = Some((set, patterns.1)),
+        Err(e) => eprintln!("WordFilter[Error] Failed to build the regex matcher: {e}")
+      }
+    }
+
+    filter
+  }
+
+  /// Load the prohibited words and the allowlist from the database
+  pub async fn load(db: &PgPool) -> Result<Self, BotError> {
+    let entries: Vec<(String, MatchMode)> = ProhibitedWords::get_words(db)
+      .await?
+      .into_iter()
+      .map(|w| {
+        let mode = MatchMode::from_str(&w.match_mode).unwrap_or(MatchMode::Exact);
+        (w.word, mode)
+      })
+      .collect();
+
+    let allowlist: Vec<String> = WordAllowlist::get_words(db).await?.into_iter().map(|w| w.word).collect();
+
+    Ok(Self::new(&entries, &allowlist))
+  }
+
+  pub fn is_match(
+    &self,
+    content: &str
+  ) -> bool {
+    self.find(content).is_some()
+  }
+
+  /// Returns the list entry that matched, if any
+  pub fn find(
+    &self,
+    content: &str
+  ) -> Option<&str> {
+    if self.words.is_empty() {
+      return None;
+    }
+
+    let scan = self.scan(content);
+    let hit = self
+      .find_exact(&scan)
+      .or_else(|| self.find_substring(&scan))
+      .or_else(|| Self::find_in_set(&self.wildcards, &scan))
+      .or_else(|| {
+        let (set, idx) = self.regexes.as_ref()?;
+        set.matches(&scan.text).iter().next().map(|i| idx[i])
+      });
+
+    hit.map(|i| self.words[i].as_str())
+  }
+
+  fn scan(
+    &self,
+    content: &str
+  ) -> Scan {
+    let folded = fold(content);
+    let mut text = String::from(" ");
+    let mut candidates = Vec::new();
+    let mut run = (String::new(), 0);
+
+    // A glued run is a word like any other, including when it's on the allowlist
+    let flush = |run: &mut (String, usize), candidates: &mut Vec<String>| {
+      if run.1 >= MIN_RUN && !self.allowlist.contains(&run.0) {
+        candidates.push(std::mem::take(&mut run.0));
+      }
+      run.0.clear();
+      run.1 = 0;
+    };
+
+    for raw in tokenise(&folded) {
+      let word: String = raw.chars().map(unleet).collect();
+      let trimmed: String = raw
+        .trim_start_matches(is_leet_symbol)
+        .trim_end_matches(|c: char| c.is_ascii_digit() || is_leet_symbol(c))
+        .chars()
+        .map(unleet)
+        .collect();
+
+      if self.allowlist.contains(&word) || self.allowlist.contains(&trimmed) {
+        flush(&mut run, &mut candidates);
+        continue;
+      }
+
+      text.push_str(&word);
+      text.push(' ');
+
+      if word.chars().count() == 1 {
+        run.0.push_str(&word);
+        run.1 += 1;
+      } else {
+        flush(&mut run, &mut candidates);
+      }
+
+      if !trimmed.is_empty() && trimmed != word {
+        candidates.push(trimmed);
+      }
+      candidates.push(word);
+    }
+    flush(&mut run, &mut candidates);
+
+    Scan { text, candidates }
+  }
+
+  fn find_exact(
+    &self,
+    scan: &Scan
+  ) -> Option<usize> {
+    for c in &scan.candidates {
+      if let Some(i) = self.exact.get(c) {
+        return Some(*i);
+      }
+
+      for suffix in SUFFIXES {
+        if let Some(i) = c.strip_suffix(suffix).and_then(|stem| self.exact.get(stem)) {
+          return Some(*i);
+        }
+      }
+    }
+
+    self.exact_phrases.iter().find(|(p, _)| scan.text.contains(p.as_str())).map(|(_, i)| *i)
+  }
+
+  fn find_substring(
+    &self,
+    scan: &Scan
+  ) -> Option<usize> {
+    let (ac, idx) = self.substrings.as_ref()?;
+
+    std::iter::once(scan.text.as_str())
+      .chain(scan.candidates.iter().map(String::as_str))
+      .find_map(|h| ac.find(h))
+      .map(|m| idx[m.pattern().as_usize()])
+  }
+
+  fn find_in_set(
+    set: &Option<(RegexSet, Vec<usize>)>,
+    scan: &Scan
+  ) -> Option<usize> {
+    let (set, idx) = set.as_ref()?;
+
+    std::iter::once(scan.text.as_str())
+      .chain(scan.candidates.iter().map(String::as_str))
+      .find_map(|h| set.matches(h).iter().next())
+      .map(|i| idx[i])
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{
+    MatchMode,
+    WordFilter
+  };
+
+  fn filter(
+    entries: &[(&str, MatchMode)],
+    allowlist: &[&str]
+  ) -> WordFilter {
+    let entries: Vec<(String, MatchMode)> = entries.iter().map(|(w, m)| (w.to_string(), *m)).collect();
+    let allowlist: Vec<String> = allowlist.iter().map(|w| w.to_string()).collect();
+    WordFilter::new(&entries, &allowlist)
+  }
+
+  #[test]
+  fn exact_matches_whole_words_and_endings() {
+    let f = filter(&[("scam", MatchMode::Exact)], &[]);
+
+    assert_eq!(f.find("this is a scam"), Some("scam"));
+    assert!(f.is_match("SCAMS everywhere"));
+    assert!(f.is_match("scam123"));
+    assert!(!f.is_match("scampi for dinner"));
+    assert!(!f.is_match("nothing to see"));
+  }
+
+  #[test]
+  fn exact_phrases_span_words() {
+    let f = filter(&[("free nitro", MatchMode::Exact)], &[]);
+
+    assert!(f.is_match("get your FREE nitro here"));
+    assert!(!f.is_match("nitro isn't free"));
+  }
+
+  #[test]
+  fn substring_matches_inside_words() {
+    let f = filter(&[("scam", MatchMode::Substring)], &[]);
+
+    assert!(f.is_match("scampi for dinner"));
+    assert!(!f.is_match("sc am"));
+  }
+
+  #[test]
+  fn wildcard_stays_within_a_word() {
+    let f = filter(&[("sc*m", MatchMode::Wildcard)], &[]);
+
+    assert!(f.is_match("what a scaaam"));
+    assert!(f.is_match("scm"));
+    assert!(!f.is_match("sc am"));
+    assert!(!f.is_match("scams"));
+  }
+
+  #[test]
+  fn regex_runs_on_the_normalised_text() {
+    let f = filter(&[("fre+ nitro", MatchMode::Regex)], &[]);
+
+    assert!(f.is_match("FR33 n1tro"));
+    assert!(!f.is_match("free"));
+  }
+
+  #[test]
+  fn lookalikes_and_leet_are_folded() {
+    let f = filter(&[("scam", MatchMode::Exact)], &[]);
+
+    assert!(f.is_match("$c4m"));
+    assert!(f.is_match("ｓｃａｍ"));
+    assert!(f.is_match("\u{455}\u{441}\u{430}m"));
+    assert!(f.is_match("sc\u{200B}am"));
+    assert!(f.is_match("s\u{336}c\u{336}a\u{336}m\u{336}"));
+  }
+
+  #[test]
+  fn glued_runs_are_whole_words() {
+    let f = filter(&[("scam", MatchMode::Exact), ("ass", MatchMode::Exact)], &[]);
+
+    assert!(f.is_match("s c a m"));
+    assert!(f.is_match("s.c.a.m"));
+    assert!(!f.is_match("c l a s s"));
+    assert!(!f.is_match("s c a"));
+  }
+
+  #[test]
+  fn allowlist_skips_words() {
+    let f = filter(&[("ass", MatchMode::Substring)], &["class", "classes"]);
+
+    assert!(!f.is_match("first class"));
+    assert!(!f.is_match("classes start soon"));
+    assert!(f.is_match("classic"));
+  }
+
+  #[test]
+  fn allowlist_covers_glued_runs() {
+    let f = filter(&[("ass", MatchMode::Substring)], &["class"]);
+
+    assert!(!f.is_match("c l a s s"));
+    assert!(!f.is_match("c.l.a.s.s"));
+  }
+}
@@ -15,6 +15,10 @@ use {
     invite_data::InviteCache,
     scheduler::spawn,
     seasonal::SeasonalTheme,
+    tasks::{
+      Monica,
+      PlayerHistory
+    },
     utils::{
       discord_token,
       token_path
@@ -41,11 +45,14 @@ use {
 type BotError = Box<dyn std::error::Error + Send + Sync>;
 
 struct BotData {
+  http:            Arc<Http>,
   redis:           Arc<controllers::cache::RedisController>,
   postgres:        sqlx::PgPool,
   serenity_bridge: Arc<LuaSerenityBridge>,
   invite_data:     Arc<InviteCache>,
-  grpc:            MonicaGRPCClient
+  grpc:            MonicaGRPCClient,
+  #[cfg(feature = "automod")]
+  automod:         Arc<controllers::automod::Automoderator>
 }
 
 #[cfg(feature = "production")]
@@ -96,15 +103,31 @@ async fn main() {
       .expect("Error initializing LuaSerenityBridge")
   );
 
+  let redis = Arc::new(controllers::cache::RedisController::new().await.unwrap());
+
+  #[cfg(feature = "automod")]
+  let automod = Arc::new(
+    controllers::automod::Automoderator::new(&postgres, Arc::clone(&redis), Arc::clone(&http))
+      .await
+      .expect("Error initializing Automoderator")
+  );
+
   let bot_data = Arc::new(BotData {
-    redis: Arc::new(controllers::cache::RedisController::new().await.unwrap()),
+    http,
+    redis,
     postgres,
     serenity_bridge,
     invite_data: Arc::new(InviteCache::new()),
-    grpc
+    grpc,
+    #[cfg(feature = "automod")]
+    automod
   });
 
   spawn(SeasonalTheme, Arc::clone(&bot_data)).await;
+  spawn(Monica::default(), Arc::clone(&bot_data)).await;
+  spawn(PlayerHistory, Arc::clone(&bot_data)).await;
+  #[cfg(feature = "automod")]
+  spawn(controllers::automod::MaliciousDomains, Arc::clone(&bot_data)).await;
 
   let prefix = if cfg!(feature = "production") {
     Some(Cow::Borrowed("!!_"))